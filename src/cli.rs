@@ -1,5 +1,6 @@
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
+use crate::models::{EpisodeOrder, MetadataFormat, NumberingStyle};
 
 /// Rename TV series ripped from makeMKV
 #[derive(Parser, Debug, Clone)]
@@ -29,6 +30,9 @@ pub enum MkvCommands {
 
   /// Exports metadata information for a series to a file
   Export(ExportArgs),
+
+  /// Checks a metadata file for problems, without accessing a processing directory
+  Validate(ValidateArgs),
 }
 
 #[derive(Args, Clone, Debug)]
@@ -59,18 +63,195 @@ pub struct RenameArgs {
   /// If this flag is specified, the encodes file will be created in the rename directory and the output directory will be created for the series or movie. No files will be renamed.
   #[clap(long, value_parser)]
   pub skip_files: bool,
+
+  /// The format of the metadata file. Defaults to the format of the file extension, or JSON if it isn't recognised.
+  #[arg(long, value_enum)]
+  pub format: Option<MetadataFormat>,
+
+  /// How episode numbers are written in the renamed files. Only used for series.
+  #[arg(long, value_enum, default_value_t = NumberingStyle::Upper)]
+  pub numbering: NumberingStyle,
+
+  /// The number of digits to pad episode numbers to. Defaults to 2, or 3 for absolute numbering.
+  /// Useful for seasons with 100 or more episodes. Only used for series, and not for date numbering.
+  #[arg(long, value_name = "digits", value_parser=clap::value_parser!(u8).range(1..=4))]
+  pub episode_width: Option<u8>,
+
+  /// Starts each episode file name with the name of the series. Example: Thundercats - 101 - The Book of Omens.mkv
+  /// Only used for series.
+  #[arg(long)]
+  pub include_series_name: bool,
+
+  /// Adds the year to series and movie directory names, such as Doctor Who (2005), to tell remakes and reboots apart.
+  /// Uses the year from the metadata, and is ignored when it is not known.
+  #[arg(long)]
+  pub include_year: bool,
+
+  /// Writes NFO files for Kodi and Jellyfin into the encode directories, holding the ids, names and air dates.
+  /// Writes tvshow.nfo for the series and an NFO named like each renamed episode, or movie.nfo for each movie.
+  #[arg(long)]
+  pub nfo: bool,
+
+  /// Downloads the poster and fanart of the series or movie, along with the season poster, into the encode directories.
+  /// Saved as poster.jpg, fanart.jpg and season01-poster.jpg. Images that already exist are kept. Requires the tvdb-api or tmdb provider.
+  #[arg(long)]
+  pub artwork: bool,
+
+  /// Places episodes directly in the series directory, without a Season XX directory. Useful for absolute numbering.
+  /// Only used for series.
+  #[arg(long)]
+  pub no_season_folder: bool,
+
+  #[command(flatten)]
+  pub provider: ProviderArgs,
+
+  #[command(flatten)]
+  pub search: SearchArgs,
 }
 
 #[derive(Args, Clone, Debug)]
 pub struct ExportArgs {
-  /// The url of TVDB season information.
-  /// Example: https://thetvdb.com/series/thundercats/seasons/official/1
-  #[arg(long, short, value_name = "url")]
-  pub url_metadata: String,
+  #[command(flatten)]
+  pub export_input_type: ExportInputType,
 
-  /// Where to extract the metadata to
+  /// Where to extract the metadata to. Use - to write to stdout
   #[arg(long, short, value_name = "path")]
-  pub export_path: PathBuf
+  pub export_path: PathBuf,
+
+  /// Overwrites the export path if it already exists
+  #[arg(long, conflicts_with = "backup")]
+  pub force: bool,
+
+  /// Renames an existing export path to <path>.bak before exporting, replacing any previous backup
+  #[arg(long)]
+  pub backup: bool,
+
+  /// The format to export to. Defaults to the format of the export path extension, or JSON if it isn't recognised.
+  #[arg(long, value_enum)]
+  pub format: Option<MetadataFormat>,
+
+  /// Exports every season of a series into a single file, which can be renamed from with --season.
+  /// The url can be for the series or any of its seasons. A series id can also be used with an API provider.
+  #[arg(long)]
+  pub all_seasons: bool,
+
+  /// Exports each season to its own file, named after the export path. Example: thundercats-s01.json
+  #[arg(long, requires = "all_seasons")]
+  pub per_season: bool,
+
+  #[command(flatten)]
+  pub provider: ProviderArgs,
+
+  #[command(flatten)]
+  pub search: SearchArgs,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ValidateArgs {
+  /// The location of the metadata file to check
+  #[arg(long, short, value_name = "file")]
+  pub file_metadata: PathBuf,
+
+  /// The format of the metadata file. Defaults to the format of the file extension, or JSON if it isn't recognised.
+  #[arg(long, value_enum)]
+  pub format: Option<MetadataFormat>,
+}
+
+// As an input is required, conflicting with the other inputs requires --search, or --file-metadata for --season.
+#[derive(Args, Clone, Debug)]
+pub struct SearchArgs {
+  /// The season to use from the chosen series, or from a metadata file that contains every season.
+  /// Defaults to 1 when searching.
+  #[arg(long, value_parser=clap::value_parser!(u32), conflicts_with_all = ["url_metadata", "html_file"])]
+  pub season: Option<u32>,
+
+  /// The release year, to narrow down movie searches
+  #[arg(long, value_parser=clap::value_parser!(u16), conflicts_with_all = ["url_metadata", "html_file"])]
+  pub year: Option<u16>,
+
+  /// Chooses the search result at this position in the list, instead of asking
+  #[arg(long, value_name = "N", value_parser=clap::value_parser!(u16).range(1..), conflicts_with_all = ["url_metadata", "html_file"])]
+  pub pick: Option<u16>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ProviderArgs {
+  /// The provider used to retrieve metadata from a url.
+  /// Defaults to tmdb for themoviedb.org urls and tvdb-scrape for all other urls.
+  #[arg(long, value_enum)]
+  pub provider: Option<ProviderType>,
+
+  /// The episode order of a series season url. Discs usually follow the DVD order.
+  /// TVDB season urls are rewritten to the matching season type. Only aired is supported by tmdb.
+  #[arg(long, value_enum)]
+  pub order: Option<EpisodeOrder>,
+
+  /// Retrieves series, episode and movie names in this language, falling back to English for any that are not translated.
+  /// Uses the provider's language codes: deu or jpn for tvdb-api, and de or ja-JP for tmdb.
+  #[arg(long, value_name = "code")]
+  pub language: Option<String>,
+
+  /// The TVDB v4 API key, from this option or the environment. Required by the tvdb-api provider.
+  #[arg(long, env = "TVDB_API_KEY", hide_env_values = true)]
+  pub tvdb_api_key: Option<String>,
+
+  /// The subscriber PIN associated with the TVDB v4 API key, if any.
+  #[arg(long, env = "TVDB_API_PIN", hide_env_values = true)]
+  pub tvdb_api_pin: Option<String>,
+
+  /// The base url of the TVDB v4 API.
+  #[arg(long, env = "TVDB_API_URL", default_value = "https://api4.thetvdb.com/v4", hide = true)]
+  pub tvdb_api_url: String,
+
+  /// The TMDB API key or read access token, from this option or the environment. Required by the tmdb provider.
+  #[arg(long, env = "TMDB_API_KEY", hide_env_values = true)]
+  pub tmdb_api_key: Option<String>,
+
+  /// The base url of the TMDB API.
+  #[arg(long, env = "TMDB_API_URL", default_value = "https://api.themoviedb.org/3", hide = true)]
+  pub tmdb_api_url: String,
+
+  /// The base url of TMDB images.
+  #[arg(long, env = "TMDB_IMAGE_URL", default_value = "https://image.tmdb.org/t/p/original", hide = true)]
+  pub tmdb_image_url: String,
+
+  #[command(flatten)]
+  pub http: HttpArgs,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct HttpArgs {
+  /// Only read metadata from the local cache, without accessing the network
+  #[arg(long)]
+  pub offline: bool,
+
+  /// Always retrieve metadata from the network, without reading or writing the local cache
+  #[arg(long, conflicts_with = "offline")]
+  pub no_cache: bool,
+
+  /// How long retrieved metadata is cached for, in hours
+  #[arg(long, value_name = "hours", default_value_t = 24)]
+  pub cache_ttl: u64,
+
+  /// Where retrieved metadata is cached. Defaults to $XDG_CACHE_HOME/mkv-renamer or ~/.cache/mkv-renamer
+  #[arg(long, env = "MKV_RENAMER_CACHE_DIR", value_name = "dir")]
+  pub cache_dir: Option<PathBuf>,
+
+  /// How long to wait for a metadata provider to respond, in seconds
+  #[arg(long, value_name = "seconds", default_value_t = 30)]
+  pub timeout: u64,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum ProviderType {
+  /// Scrape the TVDB website
+  TvdbScrape,
+
+  /// Use the TVDB v4 API
+  TvdbApi,
+
+  /// Use the TMDB API
+  Tmdb,
 }
 
 #[derive(Clone, Debug, ValueEnum)]
@@ -84,16 +265,50 @@ pub enum InputType {
 #[group(required = true, multiple = false)]
 pub struct MetadataInputType {
 
-  /// The url of TVDB season information.
+  /// The url of TVDB or TMDB season information.
   /// Example: https://thetvdb.com/series/thundercats/seasons/official/1
+  /// or https://www.themoviedb.org/tv/1399-game-of-thrones/season/1
   #[arg(long, short, value_name = "url")]
   pub url_metadata: Option<String>,
 
   /// The location of series metadata file. This depends on the input_type specified
   /// An example formats can be found at: https://raw.githubusercontent.com/ssanj/mkv-renamer/main/series-sample.conf
   /// and https://raw.githubusercontent.com/ssanj/mkv-renamer/main/movie-sample.conf
-  #[arg(long, short, value_name = "file")]
-  pub file_metadata: Option<String>
+  /// Movie box sets, where each disc is a different movie, can be found at:
+  /// https://raw.githubusercontent.com/ssanj/mkv-renamer/main/movie-box-set-sample.conf
+  #[arg(long, short, value_name = "file", conflicts_with_all = ["year", "pick"])]
+  pub file_metadata: Option<String>,
+
+  /// The location of a TVDB season or movie page saved from a browser.
+  /// The page is read in the same way as a TVDB url, without accessing the network.
+  #[arg(long, value_name = "file")]
+  pub html_file: Option<String>,
+
+  /// The name of a series or movie to search for. Requires the tvdb-api or tmdb provider.
+  /// Matches are listed so that one can be chosen.
+  #[arg(long, value_name = "name")]
+  pub search: Option<String>
+}
+
+#[derive(Args, Clone, Debug)]
+#[group(required = true, multiple = false)]
+pub struct ExportInputType {
+
+  /// The url of TVDB or TMDB season information.
+  /// Example: https://thetvdb.com/series/thundercats/seasons/official/1
+  /// or https://www.themoviedb.org/tv/1399-game-of-thrones/season/1
+  #[arg(long, short, value_name = "url")]
+  pub url_metadata: Option<String>,
+
+  /// The location of a TVDB season or movie page saved from a browser.
+  /// The page is read in the same way as a TVDB url, without accessing the network.
+  #[arg(long, value_name = "file")]
+  pub html_file: Option<String>,
+
+  /// The name of a series or movie to search for. Requires the tvdb-api or tmdb provider.
+  /// Matches are listed so that one can be chosen.
+  #[arg(long, value_name = "name")]
+  pub search: Option<String>
 }
 
 
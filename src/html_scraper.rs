@@ -1,190 +1,453 @@
-use crate::models::{EpisodeDefinition, EpisodesDefinition, MovieDefinition, SeriesMetaData};
-use scraper::{Html, Selector};
+use crate::models::{AirDate, EpisodeDefinition, EpisodeNumber, EpisodeOrder, EpisodesDefinition, MovieDefinition, RenamerError, SeasonNumber, SeriesMetaData};
+use scraper::{ElementRef, Html, Selector};
+use crate::metadata_downloader::url_path_segments;
 
-/// This whole class is pretty loose with the error handling
-/// If the HTML structure has changed, then we simply fail
-/// because this class will have to be rewritten to handle the new format
-pub fn get_series_metadata(html: &str) -> EpisodesDefinition {
+const SITE_SUFFIX: &str = "TheTVDB.com";
+
+// Season pages include the season in their title. Eg. Thundercats - Season 1 - TheTVDB.com
+const SEASON_LABELS: [&str; 5] = ["Season", "Specials", "Absolute", "DVD", "Official"];
+
+/// A scraped value along with the problems found in rows that were skipped
+#[derive(Debug)]
+pub struct Scraped<T> {
+  pub value: T,
+  pub warnings: Vec<RenamerError>,
+}
+
+/// If the HTML structure has changed, we return an error naming the element that could not be found,
+/// because this module will have to be rewritten to handle the new format.
+/// Episode rows that can't be read are skipped and reported as warnings.
+pub fn get_series_metadata(url: &str, html: &str) -> Result<Scraped<EpisodesDefinition>, RenamerError> {
   let document = Html::parse_document(html);
-  let title_selector = Selector::parse("title").unwrap();
-  let row_selector = Selector::parse("tbody tr").unwrap();
-  let column_selector = Selector::parse("td").unwrap();
-  let anchor_selector = Selector::parse("a").unwrap();
-  let tvid_selector = Selector::parse(r#"div[class="btn-group"]"#).unwrap();
+  let row_selector = selector("tbody tr");
+  let column_selector = selector("td");
+  let anchor_selector = selector("a");
 
-  let title =
-    document
-      .select(&title_selector)
-      .collect::<Vec<_>>()
-      .first()
-      .and_then(|e|
-        e
-          .inner_html()
-          .as_str()
-          .split('-')
-          .collect::<Vec<_>>()
-          .first()
-          .map(|s| s.trim().to_string())
-      )
-      .unwrap();
+  let title = get_title(url, &document, true)?;
+  let tvdb_id = get_tvdb_id(url, &document)?;
 
-  let tvid =
-    document
-      .select(&tvid_selector)
-      .collect::<Vec<_>>()
-      .first()
-      .and_then(|e|{
-        e
-          .value()
-          .attr("data-permission")
-      })
-      .unwrap();
+  // Runtime and overview columns are not on every page, so they are found by their headers
+  let runtime_column = get_column_index(&document, "Runtime");
+  let overview_column = get_column_index(&document, "Overview");
 
-    // expected format: series-TVDBID-artwork
-  let splits: Vec<_> =
-    tvid
-      .split('-')
-      .collect();
+  let mut warnings = vec![];
+  let mut episodes: Vec<EpisodeDefinition> = vec![];
 
-  let tvdb_id =
-    splits
-      .get(1) // get the second element
-      .unwrap()
-      .to_string();
+  for (index, row_fragment) in document.select(&row_selector).enumerate() {
+    let row = index + 1;
+    let columns = row_fragment.select(&column_selector).collect::<Vec<_>>();
 
-  let episodes: Vec<EpisodeDefinition> =
-    document
-      .select(&row_selector)
-      .map(|row_fragment|{
-        let required_columns =
-          row_fragment
-              .select(&column_selector)
-              .enumerate()
-              .filter_map(|(index, column)|{
-                if index == 0 { // number
-                  Option::Some(column.inner_html())
-                } else if index == 1 { // name, is within an <a href="">NAME</a>
-                  column
-                    .select(&anchor_selector)
-                    .map(|anchor|{
-                       anchor.inner_html()
-                    })
-                    .collect::<Vec<_>>()
-                    .first()
-                    .cloned()
-                } else {
-                  None
-                }
-              })
-              .map(|column|{
-                column.as_str().trim().to_owned()
-              })
-              .collect::<Vec<_>>();
-
-        let number = required_columns.first().expect("Could not get number from html").to_owned();
-        let name = required_columns.get(1).expect("Could not get name from html").to_owned();
-
-        EpisodeDefinition {
-          number,
-          name
-        }
-      }).
-      collect();
+    let number =
+      columns
+        .first()
+        .copied()
+        .map(text_of)
+        .and_then(|number| parse_episode_number(&number));
+
+    // name, is within an <a href="">NAME</a>
+    let name =
+      columns
+        .get(1)
+        .and_then(|column| column.select(&anchor_selector).next())
+        .map(text_of)
+        .filter(|name| !name.is_empty());
+
+    // The air date is optional, as episodes that have not aired yet have none
+    let air_date =
+      columns
+        .get(2)
+        .copied()
+        .map(text_of)
+        .and_then(|air_date| air_date.parse::<AirDate>().ok());
+
+    let runtime =
+      runtime_column
+        .and_then(|index| columns.get(index))
+        .copied()
+        .map(text_of)
+        .and_then(|runtime| parse_runtime(&runtime));
+
+    let overview =
+      overview_column
+        .and_then(|index| columns.get(index))
+        .copied()
+        .map(text_of)
+        .filter(|overview| !overview.is_empty());
+
+    match (number, name) {
+      (Some((number, absolute_number)), Some(name)) => episodes.push(EpisodeDefinition { number, name, absolute_number, air_date, runtime, overview }),
+      (None, _) => warnings.push(RenamerError::CouldNotScrapeEpisodeRow(url.to_owned(), row, "valid episode number".to_owned())),
+      (_, None) => warnings.push(RenamerError::CouldNotScrapeEpisodeRow(url.to_owned(), row, "episode name".to_owned())),
+    }
+  }
 
-    let season_number = get_season_number(&episodes);
+  let season_number = get_season_number(url, &episodes)?;
+  let is_absolute_order = episodes.iter().any(|e| e.absolute_number.is_some());
+  let year = get_first_aired_year(season_number, &episodes);
 
-    let metadata =
-      SeriesMetaData {
-        name: title,
-        tvdb_id,
-        season_number
-      };
+  let metadata =
+    SeriesMetaData {
+      name: title,
+      tvdb_id: Some(tvdb_id),
+      tmdb_id: None,
+      imdb_id: None,
+      season_number,
+      order: is_absolute_order.then_some(EpisodeOrder::Absolute),
+      language: None,
+      year
+    };
 
-    EpisodesDefinition {
+  Ok(
+    Scraped {
+      value: EpisodesDefinition {
         metadata,
         episodes,
+        extras: vec![],
+      },
+      warnings
     }
+  )
 }
 
-fn get_season_number(episodes: &[EpisodeDefinition]) -> String {
-  let season_number: String =
-      episodes
-        .first()
-        .map(|e| {
-          e
-            .number
-            .chars()
-            .take_while(|c| c.ne(&'E')) // Given: S01E02, take everything up to the E: S01
-            .skip_while(|c| c.is_alphabetic()) // Drop the S: 01
-            .collect()
-        })
-        .unwrap();
+// Absolute order pages number episodes across the whole series, such as 101, which are read as a single season.
+// Given: 101, returns: (S01E101, Some(101))
+fn parse_episode_number(number: &str) -> Option<(EpisodeNumber, Option<u32>)> {
+  if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+    let absolute_number = number.parse::<u32>().ok()?;
+    EpisodeNumber::from_numbers(1, absolute_number).ok().map(|n| (n, Some(absolute_number)))
+  } else {
+    number.parse::<EpisodeNumber>().ok().map(|n| (n, None))
+  }
+}
+
+fn get_column_index(document: &Html, header: &str) -> Option<usize> {
+  document
+    .select(&selector("thead th"))
+    .position(|th| text_of(th).eq_ignore_ascii_case(header))
+}
+
+// Given: 22 minutes, returns: Some(22)
+fn parse_runtime(runtime: &str) -> Option<u32> {
+  runtime
+    .split_whitespace()
+    .next()
+    .and_then(|minutes| minutes.parse::<u32>().ok())
+    .filter(|minutes| *minutes > 0)
+}
 
-      format!("{}", season_number.parse::<u8>().unwrap()) // 01 -> 1
+// Only the first season, or all episodes in absolute order, start when the series first aired
+fn get_first_aired_year(season_number: SeasonNumber, episodes: &[EpisodeDefinition]) -> Option<u16> {
+  if season_number.value() != 1 {
+    return None
+  }
+
+  episodes
+    .iter()
+    .filter_map(|e| e.air_date)
+    .min()
+    .and_then(|air_date| u16::try_from(air_date.year()).ok())
+}
+
+fn get_season_number(url: &str, episodes: &[EpisodeDefinition]) -> Result<SeasonNumber, RenamerError> {
+  episodes
+    .first()
+    .map(|e| e.number.season())
+    .ok_or_else(|| RenamerError::MissingHtmlElement(url.to_owned(), "episode rows".to_owned()))
 }
 
-pub fn get_movie_definition(html: &str) -> MovieDefinition {
+/// The official season numbers linked from a series page. Eg. /series/thundercats/seasons/official/1
+pub fn get_season_numbers(url: &str, html: &str) -> Result<Vec<u32>, RenamerError> {
   let document = Html::parse_document(html);
-  let title_selector = Selector::parse("title").unwrap();
-  let tvid_selector = Selector::parse(r#"div[class="btn-group"]"#).unwrap();
+  let anchor_selector = selector("a[href]");
 
-  let title =
+  let mut season_numbers =
+    document
+      .select(&anchor_selector)
+      .filter_map(|anchor| anchor.value().attr("href"))
+      .filter_map(|href| {
+        match url_path_segments(href).as_slice() {
+          ["series", _, "seasons", "official", season] => season.parse::<u32>().ok(),
+          _ => None
+        }
+      })
+      .collect::<Vec<_>>();
+
+  season_numbers.sort();
+  season_numbers.dedup();
+
+  if season_numbers.is_empty() {
+    Err(RenamerError::MissingHtmlElement(url.to_owned(), "season links".to_owned()))
+  } else {
+    Ok(season_numbers)
+  }
+}
+
+pub fn get_movie_definition(url: &str, html: &str) -> Result<MovieDefinition, RenamerError> {
+  let document = Html::parse_document(html);
+
+  let title = get_title(url, &document, false)?;
+  let tvdb_id = get_tvdb_id(url, &document)?;
+
+  let year = get_released_year(&document);
+
+  Ok(MovieDefinition::new(title, tvdb_id).with_year(year))
+}
+
+// The release date is listed with the other movie details. Eg. <li><strong>Released</strong><span>December 7, 2001</span></li>
+fn get_released_year(document: &Html) -> Option<u16> {
+  let strong_selector = selector("strong");
+  let span_selector = selector("span");
+
+  document
+    .select(&selector("li"))
+    .find(|li| li.select(&strong_selector).next().is_some_and(|label| text_of(label).eq_ignore_ascii_case("Released")))
+    .and_then(|li| li.select(&span_selector).next())
+    .and_then(|span| text_of(span).parse::<AirDate>().ok())
+    .and_then(|air_date| u16::try_from(air_date.year()).ok())
+}
+
+// Only the trailing site and season suffixes are removed, as names can contain hyphens. Eg. Spider-Man
+fn get_title(url: &str, document: &Html, is_season_page: bool) -> Result<String, RenamerError> {
+  let title_selector = selector("title");
+
+  let page_title =
     document
       .select(&title_selector)
-      .collect::<Vec<_>>()
-      .first()
-      .and_then(|e|
-        e
-          .inner_html()
-          .as_str()
-          .split('-')
-          .collect::<Vec<_>>()
-          .first()
-          .map(|s| s.trim().to_string())
-      )
-      .unwrap();
+      .next()
+      .map(text_of)
+      .ok_or_else(|| RenamerError::MissingHtmlElement(url.to_owned(), "<title>".to_owned()))?;
+
+  let title = strip_suffix(&page_title, |suffix| suffix.eq_ignore_ascii_case(SITE_SUFFIX));
+
+  let title =
+    if is_season_page {
+      strip_suffix(title, |suffix| SEASON_LABELS.iter().any(|label| suffix.starts_with(label)))
+    } else {
+      title
+    };
+
+  if title.is_empty() {
+    Err(RenamerError::InvalidHtmlElement(url.to_owned(), "<title>".to_owned(), page_title))
+  } else {
+    Ok(title.to_owned())
+  }
+}
+
+fn strip_suffix<F: Fn(&str) -> bool>(title: &str, is_suffix: F) -> &str {
+  match title.rsplit_once(" - ") {
+    Some((name, suffix)) if is_suffix(suffix.trim()) => name.trim(),
+    _ => title.trim()
+  }
+}
+
+// Decodes entities and removes any inner markup
+fn text_of(element: ElementRef) -> String {
+  element
+    .text()
+    .collect::<String>()
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn get_tvdb_id(url: &str, document: &Html) -> Result<String, RenamerError> {
+  let tvid_selector = selector(r#"div[class="btn-group"]"#);
 
   let tvid =
     document
       .select(&tvid_selector)
-      .collect::<Vec<_>>()
-      .first()
-      .and_then(|e|{
-        e
-          .value()
-          .attr("data-permission")
-      })
-      .unwrap();
-
-    // expected format: series-TVDBID-artwork
-  let splits: Vec<_> =
-    tvid
-      .split('-')
-      .collect();
+      .find_map(|e| e.value().attr("data-permission"))
+      .ok_or_else(|| RenamerError::MissingHtmlElement(url.to_owned(), r#"<div class="btn-group" data-permission="...">"#.to_owned()))?;
 
-  let tvdb_id =
-    splits
-      .get(1) // get the second element
-      .unwrap()
-      .to_string();
+  // expected format: series-TVDBID-artwork
+  tvid
+    .split('-')
+    .nth(1) // get the second element
+    .filter(|id| !id.is_empty())
+    .map(|id| id.to_string())
+    .ok_or_else(|| RenamerError::InvalidHtmlElement(url.to_owned(), "data-permission".to_owned(), tvid.to_owned()))
+}
 
-    MovieDefinition::new(title, tvdb_id)
+// Our selectors are static, so they are known to parse
+fn selector(value: &str) -> Selector {
+  Selector::parse(value).unwrap_or_else(|e| panic!("invalid selector: {value}, due to: {e}"))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::metadata_downloader::download_metadata;
-
     use super::*;
     use pretty_assertions::assert_eq;
 
-    // TODO: Move this to an Int test
-    #[tokio::test]
-    async fn test_get_movie_definition() {
-      let data = download_metadata("https://thetvdb.com/movies/star-wars-rise-of-skywalker").await.unwrap();
-      let result = get_movie_definition(&data);
+    const URL: &str = "https://thetvdb.com/series/thundercats/seasons/official/1";
+
+    #[test]
+    fn test_get_movie_definition() {
+      let html = include_str!("../tests/fixtures/html/star-wars-rise-of-skywalker.html");
+      let result = get_movie_definition(URL, html).unwrap();
 
       let expected = MovieDefinition::new("Star Wars: The Rise of Skywalker".to_owned(), "12879".to_owned());
       assert_eq!(result, expected)
     }
+
+    #[test]
+    fn test_specials() {
+      let html = include_str!("../tests/fixtures/html/thundercats-specials.html");
+      let result = get_series_metadata(URL, html).unwrap();
+
+      let expected_episodes =
+        vec![
+          EpisodeDefinition { number: "S00E01".parse().unwrap(), name: "The Movie".to_owned(), absolute_number: None, air_date: Some("1985-01-23".parse().unwrap()), runtime: None, overview: None },
+          EpisodeDefinition { number: "S00E02".parse().unwrap(), name: "The Return of the ThunderCats".to_owned(), absolute_number: None, air_date: Some("1986-09-08".parse().unwrap()), runtime: None, overview: None },
+        ];
+
+      assert_eq!(result.value.metadata.name, "Thundercats");
+      assert_eq!(result.value.metadata.season_number.value(), 0);
+      assert_eq!(result.value.metadata.year, None);
+      assert_eq!(result.value.episodes, expected_episodes)
+    }
+
+    #[test]
+    fn test_absolute_order() {
+      let html = include_str!("../tests/fixtures/html/thundercats-absolute.html");
+      let result = get_series_metadata("https://thetvdb.com/series/thundercats/seasons/absolute/1", html).unwrap();
+
+      let expected_episodes =
+        vec![
+          EpisodeDefinition { number: "S01E01".parse().unwrap(), name: "Exodus".to_owned(), absolute_number: Some(1), air_date: Some("1985-01-23".parse().unwrap()), runtime: Some(22), overview: Some("The ThunderCats flee their dying planet, Thundera.".to_owned()) },
+          EpisodeDefinition { number: "S01E02".parse().unwrap(), name: "The Unholy Alliance".to_owned(), absolute_number: Some(2), air_date: Some("1985-01-30".parse().unwrap()), runtime: Some(22), overview: Some("Mumm-Ra joins forces with the Mutants.".to_owned()) },
+          EpisodeDefinition { number: "S01E101".parse().unwrap(), name: "The Book of Omens".to_owned(), absolute_number: Some(101), air_date: Some("1986-11-14".parse().unwrap()), runtime: None, overview: None },
+        ];
+
+      assert_eq!(result.value.metadata.name, "Thundercats");
+      assert_eq!(result.value.metadata.season_number.value(), 1);
+      assert_eq!(result.value.metadata.order, Some(EpisodeOrder::Absolute));
+      assert_eq!(result.value.metadata.year, Some(1985));
+      assert_eq!(result.value.episodes, expected_episodes)
+    }
+
+    #[test]
+    fn test_dvd_order() {
+      let html = include_str!("../tests/fixtures/html/firefly-dvd-season-1.html");
+      let result = get_series_metadata(URL, html).unwrap();
+
+      let expected_episodes =
+        vec![
+          EpisodeDefinition { number: "S01E01".parse().unwrap(), name: "Serenity".to_owned(), absolute_number: None, air_date: Some("2002-12-20".parse().unwrap()), runtime: None, overview: None },
+          EpisodeDefinition { number: "S01E02".parse().unwrap(), name: "The Train Job".to_owned(), absolute_number: None, air_date: Some("2002-09-20".parse().unwrap()), runtime: None, overview: None },
+          EpisodeDefinition { number: "S01E03".parse().unwrap(), name: "Bushwhacked".to_owned(), absolute_number: None, air_date: Some("2002-09-27".parse().unwrap()), runtime: None, overview: None },
+        ];
+
+      assert_eq!(result.value.metadata.name, "Firefly");
+      assert_eq!(result.value.metadata.tvdb_id, Some("78874".to_owned()));
+      assert_eq!(result.value.metadata.season_number.value(), 1);
+      assert_eq!(result.value.metadata.year, Some(2002));
+      assert_eq!(result.value.episodes, expected_episodes)
+    }
+
+    #[test]
+    fn test_missing_title() {
+      let html = r#"<html><body><div class="btn-group" data-permission="series-70355-artwork"></div></body></html>"#;
+      let result = get_movie_definition(URL, html);
+
+      assert!(matches!(result, Err(RenamerError::MissingHtmlElement(url, element)) if url == URL && element == "<title>"))
+    }
+
+    #[test]
+    fn test_missing_data_permission() {
+      let html = r#"<html><head><title>Thundercats - TheTVDB.com</title></head><body><div class="btn-group"></div></body></html>"#;
+      let result = get_movie_definition(URL, html);
+
+      assert!(matches!(result, Err(RenamerError::MissingHtmlElement(_, _))))
+    }
+
+    #[test]
+    fn test_episode_rows_without_names_are_reported() {
+      let html = r#"
+        <html>
+          <head><title>Thundercats - Season 1 - TheTVDB.com</title></head>
+          <body>
+            <div class="btn-group" data-permission="series-70355-artwork"></div>
+            <table>
+              <tbody>
+                <tr><td>S01E01</td><td><a href="/1">Exodus</a></td></tr>
+                <tr><td>S01E02</td><td>No anchor</td></tr>
+                <tr><td>S01E03</td><td><a href="/3">Berbils</a></td></tr>
+              </tbody>
+            </table>
+          </body>
+        </html>"#;
+
+      let result = get_series_metadata(URL, html).unwrap();
+
+      let expected_episodes =
+        vec![
+          EpisodeDefinition { number: "S01E01".parse().unwrap(), name: "Exodus".to_owned(), absolute_number: None, air_date: None, runtime: None, overview: None },
+          EpisodeDefinition { number: "S01E03".parse().unwrap(), name: "Berbils".to_owned(), absolute_number: None, air_date: None, runtime: None, overview: None },
+        ];
+
+      assert_eq!(result.value.episodes, expected_episodes);
+      assert_eq!(result.value.metadata.season_number.value(), 1);
+      assert!(matches!(result.warnings.as_slice(), [RenamerError::CouldNotScrapeEpisodeRow(_, 2, _)]))
+    }
+
+    #[test]
+    fn test_series_title_with_hyphens_and_entities() {
+      let html = include_str!("../tests/fixtures/html/x-men-evolution-season-1.html");
+      let result = get_series_metadata(URL, html).unwrap();
+
+      let expected_episodes =
+        vec![
+          EpisodeDefinition { number: "S01E01".parse().unwrap(), name: "Strategy X".to_owned(), absolute_number: None, air_date: Some("2000-11-04".parse().unwrap()), runtime: None, overview: None },
+          EpisodeDefinition { number: "S01E02".parse().unwrap(), name: "The X-Impulse".to_owned(), absolute_number: None, air_date: Some("2000-11-11".parse().unwrap()), runtime: None, overview: None },
+          EpisodeDefinition { number: "S01E03".parse().unwrap(), name: "Rogue Recruit".to_owned(), absolute_number: None, air_date: Some("2000-11-18".parse().unwrap()), runtime: None, overview: None },
+          EpisodeDefinition { number: "S01E04".parse().unwrap(), name: "Mutant Crush".to_owned(), absolute_number: None, air_date: Some("2000-11-25".parse().unwrap()), runtime: None, overview: None },
+          EpisodeDefinition { number: "S01E05".parse().unwrap(), name: "Speed & Spyke".to_owned(), absolute_number: None, air_date: Some("2000-12-02".parse().unwrap()), runtime: None, overview: None },
+        ];
+
+      assert_eq!(result.value.metadata.name, "X-Men: Evolution");
+      assert_eq!(result.value.metadata.tvdb_id, Some("71389".to_owned()));
+      assert_eq!(result.value.metadata.year, Some(2000));
+      assert_eq!(result.value.episodes, expected_episodes);
+      assert!(result.warnings.is_empty())
+    }
+
+    #[test]
+    fn test_movie_title_with_hyphens() {
+      let html = include_str!("../tests/fixtures/html/spider-man-into-the-spider-verse.html");
+      let result = get_movie_definition(URL, html).unwrap();
+
+      assert_eq!(result, MovieDefinition::new("Spider-Man: Into the Spider-Verse".to_owned(), "3392".to_owned()))
+    }
+
+    #[test]
+    fn test_movie_title_with_entities() {
+      let html = include_str!("../tests/fixtures/html/oceans-eleven.html");
+      let result = get_movie_definition(URL, html).unwrap();
+
+      assert_eq!(result, MovieDefinition::new("Ocean's Eleven".to_owned(), "1183".to_owned()).with_year(Some(2001)))
+    }
+
+    #[test]
+    fn test_season_numbers() {
+      let html = include_str!("../tests/fixtures/html/thundercats-series.html");
+      let result = get_season_numbers(URL, html).unwrap();
+
+      assert_eq!(result, vec![0, 1, 2, 3, 4])
+    }
+
+    #[test]
+    fn test_no_season_links() {
+      let html = include_str!("../tests/fixtures/html/oceans-eleven.html");
+      let result = get_season_numbers(URL, html);
+
+      assert!(matches!(result, Err(RenamerError::MissingHtmlElement(_, element)) if element == "season links"))
+    }
+
+    #[test]
+    fn test_no_episode_rows() {
+      let html = r#"<html><head><title>Thundercats - TheTVDB.com</title></head><body><div class="btn-group" data-permission="series-70355-artwork"></div></body></html>"#;
+      let result = get_series_metadata(URL, html);
+
+      assert!(matches!(result, Err(RenamerError::MissingHtmlElement(_, element)) if element == "episode rows"))
+    }
 }
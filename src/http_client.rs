@@ -0,0 +1,373 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use reqwest::{Client, RequestBuilder, StatusCode, Url};
+use crate::cli::HttpArgs;
+use crate::models::RenamerError;
+
+mod response_cache;
+
+pub use response_cache::{CachedResponse, ResponseCache};
+
+const USER_AGENT: &str = concat!("mkv-renamer/", env!("CARGO_PKG_VERSION"), " (https://github.com/ssanj/mkv-renamer)");
+const RETRIES: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// A response body along with its status. Responses read from the cache have a status of OK, or NOT_FOUND with an empty body.
+#[derive(Debug)]
+pub struct HttpResponse {
+  pub status: StatusCode,
+  pub body: String,
+}
+
+/// A downloaded file along with its content type, when the server gave one
+#[derive(Debug)]
+pub struct HttpDownload {
+  pub bytes: Vec<u8>,
+  pub content_type: Option<String>,
+}
+
+/// A HTTP client shared by the web based providers.
+///
+/// Requests time out, and are retried with an exponential backoff when the server is unavailable or rate limits us.
+/// Proxies are read from the environment (HTTP_PROXY, HTTPS_PROXY and NO_PROXY) by reqwest.
+/// Successful and not found GET responses are cached on disk, and only the cache is read when offline.
+/// Not found responses are cached as providers can treat them as missing data, such as a missing translation.
+pub struct HttpClient {
+  client: Client,
+  cache: Option<ResponseCache>,
+  offline: bool,
+  retries: u32,
+  retry_delay: Duration,
+}
+
+impl Default for HttpClient {
+  fn default() -> Self {
+    Self::new(Duration::from_secs(30), None, false).expect("Could not create the default HTTP client") // Unexpected, so throw
+  }
+}
+
+impl HttpClient {
+  pub fn new(timeout: Duration, cache: Option<ResponseCache>, offline: bool) -> Result<Self, RenamerError> {
+    let client =
+      Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(timeout)
+        .connect_timeout(timeout)
+        .build()
+        .map_err(|e| RenamerError::CouldNotCreateHttpClient(e.to_string()))?;
+
+    Ok(
+      Self {
+        client,
+        cache,
+        offline,
+        retries: RETRIES,
+        retry_delay: RETRY_DELAY,
+      }
+    )
+  }
+
+  pub fn from_args(http_args: &HttpArgs) -> Result<Self, RenamerError> {
+    let cache =
+      if http_args.no_cache {
+        None
+      } else {
+        http_args
+          .cache_dir
+          .clone()
+          .or_else(default_cache_dir)
+          .map(|dir| ResponseCache::new(dir, Duration::from_secs(http_args.cache_ttl * 60 * 60)))
+      };
+
+    Self::new(Duration::from_secs(http_args.timeout), cache, http_args.offline)
+  }
+
+  #[cfg(test)]
+  pub fn with_retry_delay(self, retry_delay: Duration) -> Self {
+    Self { retry_delay, ..self }
+  }
+
+  /// Returns the cached response of a GET request, if there is one.
+  /// When offline, a request that is not cached is an error as it can't be sent.
+  pub fn cached(&self, url: &str, query: &[(&str, &str)]) -> Result<Option<HttpResponse>, RenamerError> {
+    let request_url = request_url(url, query)?;
+
+    let cached_response =
+      self
+        .cache
+        .as_ref()
+        .and_then(|cache| cache.get(request_url.as_str(), self.offline));
+
+    match cached_response {
+      Some(CachedResponse::Body(body)) => Ok(Some(HttpResponse { status: StatusCode::OK, body })),
+      Some(CachedResponse::NotFound) => Ok(Some(HttpResponse { status: StatusCode::NOT_FOUND, body: String::new() })),
+      None if self.offline => Err(RenamerError::NotInOfflineCache(url.to_owned())),
+      None => Ok(None)
+    }
+  }
+
+  /// Sends a GET request, using the cache if possible. The request can be authenticated before it is sent.
+  pub async fn get<F>(&self, url: &str, query: &[(&str, &str)], authenticate: F) -> Result<HttpResponse, RenamerError>
+    where F: Fn(RequestBuilder) -> RequestBuilder
+  {
+    if let Some(response) = self.cached(url, query)? {
+      return Ok(response)
+    }
+
+    let request_url = request_url(url, query)?;
+    let response = self.send(url, || authenticate(self.client.get(request_url.clone()))).await?;
+
+    if let Some(cache) = &self.cache {
+      if response.status.is_success() {
+        cache.put(request_url.as_str(), &response.body)
+      } else if response.status == StatusCode::NOT_FOUND {
+        cache.put_not_found(request_url.as_str())
+      }
+    }
+
+    Ok(response)
+  }
+
+  /// Sends a POST request with a JSON body. These are never cached.
+  pub async fn post_json(&self, url: &str, body: &str) -> Result<HttpResponse, RenamerError> {
+    if self.offline {
+      return Err(RenamerError::NotInOfflineCache(url.to_owned()))
+    }
+
+    self
+      .send(url, || {
+        self
+          .client
+          .post(url)
+          .header(reqwest::header::CONTENT_TYPE, "application/json")
+          .body(body.to_owned())
+      })
+      .await
+  }
+
+  /// Downloads a binary file, such as an image. These are never cached, as they are saved by the caller.
+  pub async fn download(&self, url: &str) -> Result<HttpDownload, RenamerError> {
+    if self.offline {
+      return Err(RenamerError::NotInOfflineCache(url.to_owned()))
+    }
+
+    let download_error = |message: String| RenamerError::CouldNotDownloadArtwork(url.to_owned(), message);
+
+    let request_url = request_url(url, &[])?;
+    let response = self.send_with_retries(|| self.client.get(request_url.clone())).await.map_err(|e| download_error(e.to_string()))?;
+    let status = response.status();
+
+    if !status.is_success() {
+      return Err(download_error(status.to_string()))
+    }
+
+    let content_type =
+      response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .map(|content_type| content_type.to_owned());
+
+    response
+      .bytes()
+      .await
+      .map(|bytes| HttpDownload { bytes: bytes.to_vec(), content_type })
+      .map_err(|e| download_error(e.to_string()))
+  }
+
+  async fn send<F>(&self, url: &str, request: F) -> Result<HttpResponse, RenamerError>
+    where F: Fn() -> RequestBuilder
+  {
+    let response = self.send_with_retries(request).await.map_err(|e| RenamerError::CouldNotAccessMetadataURL(url.to_owned(), e.to_string()))?;
+    let status = response.status();
+    let body =
+      response
+        .text()
+        .await
+        .map_err(|e| RenamerError::CouldNotDecodeMetadataBody(url.to_owned(), e.to_string()))?;
+
+    Ok(HttpResponse { status, body })
+  }
+
+  // Requests are rebuilt for each attempt, as a sent request can't be reused
+  async fn send_with_retries<F>(&self, request: F) -> Result<reqwest::Response, reqwest::Error>
+    where F: Fn() -> RequestBuilder
+  {
+    let mut attempt = 0;
+
+    loop {
+      let result = request().send().await;
+
+      let retry_after =
+        match &result {
+          Ok(response) if is_retryable(response.status()) => Some(retry_after(response)),
+          Err(e) if e.is_timeout() || e.is_connect() => Some(None),
+          _ => None
+        };
+
+      match retry_after {
+        Some(retry_after) if attempt < self.retries => {
+          let backoff = self.retry_delay * 2_u32.pow(attempt);
+          tokio::time::sleep(retry_after.unwrap_or(backoff).min(MAX_RETRY_DELAY)).await;
+          attempt += 1
+        },
+        _ => return result
+      }
+    }
+  }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+  status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+// Only the delay in seconds form of Retry-After is supported
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+  response
+    .headers()
+    .get(reqwest::header::RETRY_AFTER)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.trim().parse::<u64>().ok())
+    .map(Duration::from_secs)
+}
+
+// An empty query would leave a trailing '?' on the url
+fn request_url(url: &str, query: &[(&str, &str)]) -> Result<Url, RenamerError> {
+  let request_url =
+    if query.is_empty() {
+      Url::parse(url)
+    } else {
+      Url::parse_with_params(url, query)
+    };
+
+  request_url.map_err(|e| RenamerError::CouldNotAccessMetadataURL(url.to_owned(), e.to_string()))
+}
+
+fn default_cache_dir() -> Option<PathBuf> {
+  std::env::var_os("XDG_CACHE_HOME")
+    .filter(|dir| !dir.is_empty())
+    .map(PathBuf::from)
+    .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+    .map(|dir| dir.join("mkv-renamer"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+    use tempfile::tempdir;
+    use pretty_assertions::assert_eq;
+
+    fn client(cache: Option<ResponseCache>, offline: bool) -> HttpClient {
+      HttpClient::new(Duration::from_secs(5), cache, offline)
+        .unwrap()
+        .with_retry_delay(Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors() {
+      let mut server = Server::new_async().await;
+
+      // Once a mock has its expected hits, the next matching mock is used
+      let unavailable =
+        server
+          .mock("GET", "/page")
+          .with_status(503)
+          .expect(2)
+          .create_async()
+          .await;
+
+      let available =
+        server
+          .mock("GET", "/page")
+          .with_body("page")
+          .expect(1)
+          .create_async()
+          .await;
+
+      let url = format!("{}/page", server.url());
+      let response = client(None, false).get(&url, &[], |r| r).await.unwrap();
+
+      assert_eq!(response.status, StatusCode::OK);
+      assert_eq!(response.body, "page");
+      unavailable.assert_async().await;
+      available.assert_async().await
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_retries() {
+      let mut server = Server::new_async().await;
+
+      let rate_limited =
+        server
+          .mock("GET", "/page")
+          .with_status(429)
+          .expect(usize::try_from(RETRIES).unwrap() + 1)
+          .create_async()
+          .await;
+
+      let url = format!("{}/page", server.url());
+      let response = client(None, false).get(&url, &[], |r| r).await.unwrap();
+
+      assert_eq!(response.status, StatusCode::TOO_MANY_REQUESTS);
+      rate_limited.assert_async().await
+    }
+
+    #[tokio::test]
+    async fn test_sends_user_agent() {
+      let mut server = Server::new_async().await;
+
+      let page =
+        server
+          .mock("GET", "/page")
+          .match_header("user-agent", USER_AGENT)
+          .with_body("page")
+          .create_async()
+          .await;
+
+      let url = format!("{}/page", server.url());
+      client(None, false).get(&url, &[], |r| r).await.unwrap();
+
+      page.assert_async().await
+    }
+
+    #[tokio::test]
+    async fn test_caches_successful_responses() {
+      let mut server = Server::new_async().await;
+      let cache_dir = tempdir().unwrap();
+
+      let page =
+        server
+          .mock("GET", "/page")
+          .match_query(mockito::Matcher::UrlEncoded("season".into(), "1".into()))
+          .with_body("page")
+          .expect(1)
+          .create_async()
+          .await;
+
+      let url = format!("{}/page", server.url());
+      let cache = || Some(ResponseCache::new(cache_dir.path().to_owned(), Duration::from_secs(60)));
+
+      let response = client(cache(), false).get(&url, &[("season", "1")], |r| r).await.unwrap();
+      assert_eq!(response.body, "page");
+
+      let cached_response = client(cache(), false).get(&url, &[("season", "1")], |r| r).await.unwrap();
+      assert_eq!(cached_response.body, "page");
+
+      let offline_response = client(cache(), true).get(&url, &[("season", "1")], |r| r).await.unwrap();
+      assert_eq!(offline_response.body, "page");
+
+      page.assert_async().await
+    }
+
+    #[tokio::test]
+    async fn test_offline_without_cached_response() {
+      let cache_dir = tempdir().unwrap();
+      let cache = ResponseCache::new(cache_dir.path().to_owned(), Duration::from_secs(60));
+
+      let result = client(Some(cache), true).get("https://thetvdb.com/series/thundercats/seasons/official/1", &[], |r| r).await;
+
+      assert!(matches!(result, Err(RenamerError::NotInOfflineCache(_))))
+    }
+}
@@ -0,0 +1,133 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Response bodies stored on disk, keyed by a hash of the request url.
+/// Urls that were not found are stored as an empty marker file, so they are also known when offline.
+///
+/// Urls can contain API keys, so only their hash is written to disk.
+/// Failures to read or write the cache are treated as a cache miss, as the network can be used instead.
+#[derive(Debug, PartialEq)]
+pub enum CachedResponse {
+  Body(String),
+  NotFound,
+}
+
+pub struct ResponseCache {
+  dir: PathBuf,
+  ttl: Duration,
+}
+
+impl ResponseCache {
+  pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+    Self {
+      dir,
+      ttl
+    }
+  }
+
+  /// Returns the response for the url if it has been cached within the TTL, or at any time if `ignore_ttl` is set
+  pub fn get(&self, url: &str, ignore_ttl: bool) -> Option<CachedResponse> {
+    let path = self.path(url);
+    let not_found_path = self.not_found_path(url);
+
+    if self.is_fresh(&path, ignore_ttl) {
+      fs::read_to_string(path).ok().map(CachedResponse::Body)
+    } else if self.is_fresh(&not_found_path, ignore_ttl) {
+      Some(CachedResponse::NotFound)
+    } else {
+      None
+    }
+  }
+
+  pub fn put(&self, url: &str, body: &str) {
+    let _ = fs::remove_file(self.not_found_path(url));
+    let _ =
+      fs::create_dir_all(&self.dir)
+        .and_then(|_| fs::write(self.path(url), body));
+  }
+
+  pub fn put_not_found(&self, url: &str) {
+    let _ = fs::remove_file(self.path(url));
+    let _ =
+      fs::create_dir_all(&self.dir)
+        .and_then(|_| fs::write(self.not_found_path(url), ""));
+  }
+
+  fn is_fresh(&self, path: &Path, ignore_ttl: bool) -> bool {
+    fs::metadata(path)
+      .and_then(|metadata| metadata.modified())
+      .ok()
+      .map(|modified| ignore_ttl || SystemTime::now().duration_since(modified).unwrap_or_default() < self.ttl)
+      .unwrap_or(false)
+  }
+
+  fn path(&self, url: &str) -> PathBuf {
+    self.dir.join(format!("{:016x}", fnv1a(url)))
+  }
+
+  fn not_found_path(&self, url: &str) -> PathBuf {
+    self.dir.join(format!("{:016x}.not-found", fnv1a(url)))
+  }
+}
+
+// FNV-1a is stable across releases, unlike the standard library hasher
+fn fnv1a(value: &str) -> u64 {
+  value
+    .bytes()
+    .fold(0xcbf29ce484222325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use pretty_assertions::assert_eq;
+
+    const URL: &str = "https://thetvdb.com/series/thundercats/seasons/official/1";
+
+    #[test]
+    fn test_get_cached_body() {
+      let cache_dir = tempdir().unwrap();
+      let cache = ResponseCache::new(cache_dir.path().join("cache"), Duration::from_secs(60));
+
+      assert_eq!(cache.get(URL, false), None);
+
+      cache.put(URL, "page");
+
+      assert_eq!(cache.get(URL, false), Some(CachedResponse::Body("page".to_owned())));
+      assert_eq!(cache.get("https://thetvdb.com/series/thundercats/seasons/official/2", false), None)
+    }
+
+    #[test]
+    fn test_expired_body() {
+      let cache_dir = tempdir().unwrap();
+      let cache = ResponseCache::new(cache_dir.path().to_owned(), Duration::ZERO);
+
+      cache.put(URL, "page");
+
+      assert_eq!(cache.get(URL, false), None);
+      assert_eq!(cache.get(URL, true), Some(CachedResponse::Body("page".to_owned())))
+    }
+
+    #[test]
+    fn test_not_found() {
+      let cache_dir = tempdir().unwrap();
+      let cache = ResponseCache::new(cache_dir.path().to_owned(), Duration::from_secs(60));
+
+      cache.put_not_found(URL);
+      assert_eq!(cache.get(URL, false), Some(CachedResponse::NotFound));
+
+      cache.put(URL, "page");
+      assert_eq!(cache.get(URL, false), Some(CachedResponse::Body("page".to_owned())));
+
+      cache.put_not_found(URL);
+      assert_eq!(cache.get(URL, false), Some(CachedResponse::NotFound))
+    }
+
+    #[test]
+    fn test_fnv1a() {
+      assert_eq!(fnv1a(""), 0xcbf29ce484222325);
+      assert_eq!(fnv1a("a"), 0xaf63dc4c8601ec8c)
+    }
+}
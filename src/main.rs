@@ -1,7 +1,13 @@
 mod models;
 mod cli;
 mod metadata_downloader;
+mod http_client;
+mod metadata_provider;
 mod html_scraper;
+mod mkv_duration;
+mod nfo;
+mod tvdb_api;
+mod tmdb_api;
 mod workflow;
 
 use cli::get_cli_args;
@@ -16,6 +22,9 @@ async fn main() {
   match perform_workflow(config).await {
     Ok(Output::Success) => println!("{}", style("Renaming completed successfully").green()),
     Ok(Output::UserCanceled) => println!("{}", style("User canceled rename").color256(173)),
+    // stdout holds the export, so it can be piped
+    Ok(Output::Valid) => println!("{}", style("Metadata file is valid").green()),
+    Ok(Output::ExportedToStdout) => eprintln!("{}", style("Export completed successfully").green()),
     Err(e) => eprintln!("{}", style(e)),
   }
 }
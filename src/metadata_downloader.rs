@@ -1,13 +1,37 @@
+use serde::de::DeserializeOwned;
+use crate::http_client::{HttpClient, HttpResponse};
 use crate::models::RenamerError;
 
-pub async fn download_metadata(url: &str) -> Result<String, RenamerError> {
-  let body =
-    reqwest::get(url)
-      .await
-      .map_err(|e| RenamerError::CouldNotAccessMetadataURL(url.to_owned(), e.to_string()))?
-      .text()
-      .await
-      .map_err(|e| RenamerError::CouldNotDecodeMetadataBody(url.to_owned(), e.to_string()))?;
+pub async fn download_metadata(http: &HttpClient, url: &str) -> Result<String, RenamerError> {
+  let response = http.get(url, &[], |request| request).await?;
 
-  Ok(body)
+  if response.status.is_success() {
+    Ok(response.body)
+  } else {
+    Err(RenamerError::CouldNotAccessMetadataURL(url.to_owned(), response.status.to_string()))
+  }
+}
+
+pub fn decode_json_response<T: DeserializeOwned>(url: &str, response: HttpResponse) -> Result<T, RenamerError> {
+  if !response.status.is_success() {
+    return Err(RenamerError::CouldNotAccessMetadataURL(url.to_owned(), response.status.to_string()))
+  }
+
+  serde_json::from_str(&response.body)
+    .map_err(|e| RenamerError::CouldNotDecodeMetadataBody(url.to_owned(), e.to_string()))
+}
+
+/// Everything after the host, ignoring any query string or fragment
+pub fn url_path_segments(url: &str) -> Vec<&str> {
+  url
+    .split(['?', '#'])
+    .next()
+    .unwrap_or_default()
+    .split("://")
+    .last()
+    .unwrap_or_default()
+    .split('/')
+    .skip(1) // host
+    .filter(|s| !s.is_empty())
+    .collect()
 }
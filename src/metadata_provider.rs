@@ -0,0 +1,358 @@
+use crate::cli::{ExportInputType, MetadataInputType, ProviderArgs, ProviderType};
+use crate::http_client::HttpClient;
+use crate::models::*;
+use crate::tmdb_api::{TmdbApiClient, TMDB_HOST};
+use crate::tvdb_api::{TvdbApiClient, TvdbSeasonUrl};
+
+mod tvdb_scrape;
+mod file;
+mod html_file;
+#[cfg(test)]
+mod in_memory;
+
+pub use tvdb_scrape::TvdbScrapeProvider;
+pub use file::{read_input_from_file, FileProvider};
+pub use html_file::HtmlFileProvider;
+#[cfg(test)]
+pub use in_memory::InMemoryProvider;
+
+/// A source of series and movie metadata.
+///
+/// The `source` passed to each method is provider specific. For example a URL for the web based providers
+/// and a path for the file provider. The `source` of each search result can be passed back to the same provider.
+pub trait MetadataProvider {
+  async fn series_season(&self, source: &str) -> Result<EpisodesDefinition, RenamerError>;
+
+  async fn movie(&self, source: &str) -> Result<MovieMetadata, RenamerError>;
+
+  /// The sources of every season of a series, given the source of the series or any of its seasons
+  async fn season_sources(&self, source: &str) -> Result<Vec<String>, RenamerError>;
+
+  async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchResult>, RenamerError>;
+
+  /// The series poster and fanart, along with the poster of the season
+  async fn series_artwork(&self, source: &str) -> Result<Artwork, RenamerError>;
+
+  async fn movie_artwork(&self, source: &str) -> Result<Artwork, RenamerError>;
+
+  /// The client web based providers retrieve metadata with, which is also used to download their artwork
+  fn http_client(&self) -> Option<&HttpClient>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MediaKind {
+  Series,
+  Movie,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+  pub kind: MediaKind,
+  pub name: String,
+  pub year: Option<u16>,
+  /// The season to use in the source of series results
+  pub season: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+  pub id: String,
+  pub name: String,
+  pub year: Option<String>,
+  pub source: String,
+}
+
+/// The available providers, chosen by flag or by the metadata source
+pub enum Provider {
+  TvdbScrape(TvdbScrapeProvider),
+  TvdbApi(TvdbApiClient),
+  Tmdb(TmdbApiClient),
+  File(FileProvider),
+  HtmlFile(HtmlFileProvider),
+}
+
+impl Provider {
+  /// Uses the provider if supplied, otherwise chooses one based on the url.
+  /// Returns the provider along with the url for the selected episode order.
+  pub fn for_url(url: &str, provider_args: &ProviderArgs) -> Result<(Self, String), RenamerError> {
+    let provider_type = provider_type(url, provider_args);
+    let source = ordered_url(url, &provider_type, provider_args.order)?;
+    let provider = Self::for_type(&provider_type, provider_args)?;
+
+    Ok((provider, source))
+  }
+
+  /// Uses the provider if supplied, otherwise chooses an API provider that has a key.
+  /// Returns the provider along with its type.
+  pub fn for_search(provider_args: &ProviderArgs) -> Result<(Self, ProviderType), RenamerError> {
+    let provider_type =
+      match (&provider_args.provider, &provider_args.tvdb_api_key, &provider_args.tmdb_api_key) {
+        (Some(provider_type), _, _) => provider_type.clone(),
+        (None, Some(_), _) => ProviderType::TvdbApi,
+        (None, None, Some(_)) => ProviderType::Tmdb,
+        (None, None, None) => return Err(RenamerError::NoSearchProvider),
+      };
+
+    Self::for_type(&provider_type, provider_args).map(|provider| (provider, provider_type))
+  }
+
+  /// Returns the url for the episode order, for providers that support it
+  pub fn with_order(&self, url: &str, order: Option<EpisodeOrder>) -> Result<String, RenamerError> {
+    match self {
+      Provider::TvdbScrape(_) => ordered_url(url, &ProviderType::TvdbScrape, order),
+      Provider::TvdbApi(_) => ordered_url(url, &ProviderType::TvdbApi, order),
+      Provider::Tmdb(_) => ordered_url(url, &ProviderType::Tmdb, order),
+      Provider::File(_) | Provider::HtmlFile(_) => Ok(url.to_owned()),
+    }
+  }
+
+  fn for_type(provider_type: &ProviderType, provider_args: &ProviderArgs) -> Result<Self, RenamerError> {
+    match provider_type {
+      ProviderType::TvdbScrape if provider_args.language.is_some() => Err(RenamerError::LanguageNotSupported("tvdb-scrape".to_owned())),
+      ProviderType::TvdbScrape => HttpClient::from_args(&provider_args.http).map(|http| Provider::TvdbScrape(TvdbScrapeProvider::new(http))),
+      ProviderType::TvdbApi => TvdbApiClient::from_args(provider_args).map(Provider::TvdbApi),
+      ProviderType::Tmdb => TmdbApiClient::from_args(provider_args).map(Provider::Tmdb),
+    }
+  }
+
+  /// Returns the provider along with the source to pass to it
+  /// The season is used to choose from metadata files that contain every season of a series.
+  pub fn for_input(metadata_input_type: &MetadataInputType, provider_args: &ProviderArgs, season: Option<u32>, format: Option<MetadataFormat>) -> Result<(Self, String), RenamerError> {
+    match get_metadata_type(metadata_input_type) {
+      ConfigMetadataInputType::Url(url) => Self::for_url(&url, provider_args),
+      ConfigMetadataInputType::File(file) => Ok((Provider::File(FileProvider::new(season, format)), file)),
+      ConfigMetadataInputType::HtmlFile(file) => html_file_provider(file, provider_args),
+      ConfigMetadataInputType::Invalid => Err(RenamerError::InvalidMetadataConfiguration(format!("{:?}", metadata_input_type))),
+    }
+  }
+
+  /// Returns the provider along with the source to export from
+  pub fn for_export(export_input_type: &ExportInputType, provider_args: &ProviderArgs) -> Result<(Self, String), RenamerError> {
+    match (export_input_type.clone().url_metadata, export_input_type.clone().html_file) {
+      (Some(url), _) => Self::for_url(&url, provider_args),
+      (_, Some(file)) => html_file_provider(file, provider_args),
+      _ => Err(RenamerError::InvalidMetadataConfiguration(format!("{:?}", export_input_type))),
+    }
+  }
+}
+
+impl MetadataProvider for Provider {
+  async fn series_season(&self, source: &str) -> Result<EpisodesDefinition, RenamerError> {
+    match self {
+      Provider::TvdbScrape(provider) => provider.series_season(source).await,
+      Provider::TvdbApi(provider) => provider.series_season(source).await,
+      Provider::Tmdb(provider) => provider.series_season(source).await,
+      Provider::File(provider) => provider.series_season(source).await,
+      Provider::HtmlFile(provider) => provider.series_season(source).await,
+    }
+  }
+
+  async fn movie(&self, source: &str) -> Result<MovieMetadata, RenamerError> {
+    match self {
+      Provider::TvdbScrape(provider) => provider.movie(source).await,
+      Provider::TvdbApi(provider) => provider.movie(source).await,
+      Provider::Tmdb(provider) => provider.movie(source).await,
+      Provider::File(provider) => provider.movie(source).await,
+      Provider::HtmlFile(provider) => provider.movie(source).await,
+    }
+  }
+
+  async fn season_sources(&self, source: &str) -> Result<Vec<String>, RenamerError> {
+    match self {
+      Provider::TvdbScrape(provider) => provider.season_sources(source).await,
+      Provider::TvdbApi(provider) => provider.season_sources(source).await,
+      Provider::Tmdb(provider) => provider.season_sources(source).await,
+      Provider::File(provider) => provider.season_sources(source).await,
+      Provider::HtmlFile(provider) => provider.season_sources(source).await,
+    }
+  }
+
+  async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchResult>, RenamerError> {
+    match self {
+      Provider::TvdbScrape(provider) => provider.search(query).await,
+      Provider::TvdbApi(provider) => provider.search(query).await,
+      Provider::Tmdb(provider) => provider.search(query).await,
+      Provider::File(provider) => provider.search(query).await,
+      Provider::HtmlFile(provider) => provider.search(query).await,
+    }
+  }
+
+  async fn series_artwork(&self, source: &str) -> Result<Artwork, RenamerError> {
+    match self {
+      Provider::TvdbScrape(provider) => provider.series_artwork(source).await,
+      Provider::TvdbApi(provider) => provider.series_artwork(source).await,
+      Provider::Tmdb(provider) => provider.series_artwork(source).await,
+      Provider::File(provider) => provider.series_artwork(source).await,
+      Provider::HtmlFile(provider) => provider.series_artwork(source).await,
+    }
+  }
+
+  async fn movie_artwork(&self, source: &str) -> Result<Artwork, RenamerError> {
+    match self {
+      Provider::TvdbScrape(provider) => provider.movie_artwork(source).await,
+      Provider::TvdbApi(provider) => provider.movie_artwork(source).await,
+      Provider::Tmdb(provider) => provider.movie_artwork(source).await,
+      Provider::File(provider) => provider.movie_artwork(source).await,
+      Provider::HtmlFile(provider) => provider.movie_artwork(source).await,
+    }
+  }
+
+  fn http_client(&self) -> Option<&HttpClient> {
+    match self {
+      Provider::TvdbScrape(provider) => provider.http_client(),
+      Provider::TvdbApi(provider) => provider.http_client(),
+      Provider::Tmdb(provider) => provider.http_client(),
+      Provider::File(provider) => provider.http_client(),
+      Provider::HtmlFile(provider) => provider.http_client(),
+    }
+  }
+}
+
+// Saved pages are in the language they were saved in
+fn html_file_provider(file: String, provider_args: &ProviderArgs) -> Result<(Provider, String), RenamerError> {
+  if provider_args.language.is_some() {
+    Err(RenamerError::LanguageNotSupported("html-file".to_owned()))
+  } else {
+    Ok((Provider::HtmlFile(HtmlFileProvider), file))
+  }
+}
+
+fn get_metadata_type(input_type: &MetadataInputType) -> ConfigMetadataInputType {
+  match (input_type.clone().url_metadata, input_type.clone().file_metadata, input_type.clone().html_file) {
+    (Some(url), _, _) => ConfigMetadataInputType::Url(url),
+    (_, Some(file), _) => ConfigMetadataInputType::File(file),
+    (_, _, Some(html_file)) => ConfigMetadataInputType::HtmlFile(html_file),
+    _ => ConfigMetadataInputType::Invalid
+  }
+}
+
+fn provider_type(url: &str, provider_args: &ProviderArgs) -> ProviderType {
+  provider_args
+    .provider
+    .clone()
+    .unwrap_or_else(|| {
+      let host = url.split("://").last().unwrap_or_default().split('/').next().unwrap_or_default();
+      if host == TMDB_HOST || host.ends_with(&format!(".{TMDB_HOST}")) {
+        ProviderType::Tmdb
+      } else {
+        ProviderType::TvdbScrape
+      }
+    })
+}
+
+/// The translated text when there is some, otherwise the text in the provider's default language
+pub fn translated(translation: Option<String>, default: String) -> String {
+  translation
+    .filter(|t| !t.trim().is_empty())
+    .unwrap_or(default)
+}
+
+/// Orders results with an exact name match first, followed by those released in the requested year.
+/// Otherwise the order from the provider is kept.
+pub fn rank_results(mut results: Vec<SearchResult>, query: &SearchQuery) -> Vec<SearchResult> {
+  let rank = |result: &SearchResult| {
+    let name_matches = result.name.eq_ignore_ascii_case(query.name.trim());
+    let year_matches = query.year.is_some_and(|year| result.year.as_deref() == Some(year.to_string().as_str()));
+    (!name_matches, !year_matches)
+  };
+
+  results.sort_by_key(rank);
+  results
+}
+
+// TVDB season urls include the season type, so we rewrite them for the requested order.
+// Urls that aren't season urls, such as movie urls, are left as is.
+pub fn ordered_url(url: &str, provider_type: &ProviderType, order: Option<EpisodeOrder>) -> Result<String, RenamerError> {
+  match (order, provider_type) {
+    (None, _) => Ok(url.to_owned()),
+    (Some(order), ProviderType::TvdbScrape | ProviderType::TvdbApi) =>
+      Ok(
+        TvdbSeasonUrl::parse(url)
+          .map(|season_url| TvdbSeasonUrl { season_type: order.into(), ..season_url }.to_url())
+          .unwrap_or_else(|_| url.to_owned())
+      ),
+    (Some(EpisodeOrder::Aired), ProviderType::Tmdb) => Ok(url.to_owned()),
+    (Some(order), ProviderType::Tmdb) => Err(RenamerError::EpisodeOrderNotSupported("tmdb".to_owned(), order.as_str().to_owned())),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use crate::cli::{MkvCommands, MkvInputType, MkvRenamerArgs};
+
+    fn provider_args(args: &[&str]) -> ProviderArgs {
+      let args = [&["mkv-renamer", "series", "export", "-u", "url", "-e", "export.json"], args].concat();
+      match MkvRenamerArgs::parse_from(args).commands {
+        MkvInputType::Series(MkvCommands::Export(export_args)) => export_args.provider,
+        _ => unreachable!()
+      }
+    }
+
+    // The keys are set here rather than parsed, as clap would read any TVDB_API_KEY or TMDB_API_KEY in the environment
+    fn provider_args_with_keys(args: &[&str], tvdb_api_key: Option<&str>, tmdb_api_key: Option<&str>) -> ProviderArgs {
+      ProviderArgs {
+        tvdb_api_key: tvdb_api_key.map(str::to_owned),
+        tmdb_api_key: tmdb_api_key.map(str::to_owned),
+        ..provider_args(args)
+      }
+    }
+
+    #[test]
+    fn test_provider_type_from_url() {
+      assert!(matches!(provider_type("https://www.themoviedb.org/movie/603-the-matrix", &provider_args(&[])), ProviderType::Tmdb));
+      assert!(matches!(provider_type("https://thetvdb.com/movies/the-matrix", &provider_args(&[])), ProviderType::TvdbScrape));
+      assert!(matches!(provider_type("https://thetvdb.com/movies/the-matrix", &provider_args(&["--provider", "tvdb-api"])), ProviderType::TvdbApi))
+    }
+
+    #[test]
+    fn test_provider_for_search() {
+      assert!(matches!(Provider::for_search(&provider_args_with_keys(&[], None, Some("key"))), Ok((Provider::Tmdb(_), ProviderType::Tmdb))));
+      assert!(matches!(Provider::for_search(&provider_args_with_keys(&[], Some("key"), Some("key"))), Ok((Provider::TvdbApi(_), ProviderType::TvdbApi))));
+      assert!(matches!(Provider::for_search(&provider_args_with_keys(&["--provider", "tvdb-scrape"], None, None)), Ok((Provider::TvdbScrape(_), ProviderType::TvdbScrape))))
+    }
+
+    #[test]
+    fn test_language_requires_api_provider() {
+      let provider_args = provider_args(&["--language", "deu"]);
+
+      assert!(matches!(Provider::for_url("https://thetvdb.com/series/thundercats/seasons/official/1", &provider_args), Err(RenamerError::LanguageNotSupported(_))));
+      assert!(matches!(html_file_provider("thundercats.html".to_owned(), &provider_args), Err(RenamerError::LanguageNotSupported(_))))
+    }
+
+    #[test]
+    fn test_rank_results() {
+      let result = |id: &str, name: &str, year: &str| SearchResult { id: id.to_owned(), name: name.to_owned(), year: Some(year.to_owned()), source: id.to_owned() };
+
+      let results =
+        vec![
+          result("1", "Ocean's Eleven (Collection)", "2001"),
+          result("2", "Ocean's Eleven", "1960"),
+          result("3", "Ocean's Eleven", "2001"),
+        ];
+
+      let query = SearchQuery { kind: MediaKind::Movie, name: "ocean's eleven".to_owned(), year: Some(2001), season: 1 };
+      let ranked = rank_results(results, &query).into_iter().map(|r| r.id).collect::<Vec<_>>();
+
+      assert_eq!(ranked, vec!["3", "2", "1"])
+    }
+
+    #[test]
+    fn test_ordered_url() {
+      let url = "https://thetvdb.com/series/firefly/seasons/official/1";
+
+      assert_eq!(ordered_url(url, &ProviderType::TvdbScrape, None).unwrap(), url);
+      assert_eq!(ordered_url(url, &ProviderType::TvdbScrape, Some(EpisodeOrder::Dvd)).unwrap(), "https://thetvdb.com/series/firefly/seasons/dvd/1");
+      assert_eq!(ordered_url(url, &ProviderType::TvdbApi, Some(EpisodeOrder::Absolute)).unwrap(), "https://thetvdb.com/series/firefly/seasons/absolute/1");
+      assert_eq!(ordered_url("https://thetvdb.com/movies/serenity", &ProviderType::TvdbScrape, Some(EpisodeOrder::Dvd)).unwrap(), "https://thetvdb.com/movies/serenity")
+    }
+
+    #[test]
+    fn test_ordered_url_for_tmdb() {
+      let url = "https://www.themoviedb.org/tv/1437-firefly/season/1";
+
+      assert_eq!(ordered_url(url, &ProviderType::Tmdb, Some(EpisodeOrder::Aired)).unwrap(), url);
+      assert!(matches!(ordered_url(url, &ProviderType::Tmdb, Some(EpisodeOrder::Dvd)), Err(RenamerError::EpisodeOrderNotSupported(_, _))))
+    }
+}
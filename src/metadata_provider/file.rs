@@ -0,0 +1,174 @@
+use std::fs;
+use std::path::Path;
+use crate::http_client::HttpClient;
+use crate::models::*;
+use super::{MetadataProvider, SearchQuery, SearchResult};
+
+/// Reads metadata from a local file, in the format given or the format of its extension.
+/// Series files can contain every season of a series, in which case the season is chosen with `season`.
+pub struct FileProvider {
+  season: Option<u32>,
+  format: Option<MetadataFormat>,
+}
+
+impl FileProvider {
+  pub fn new(season: Option<u32>, format: Option<MetadataFormat>) -> Self {
+    Self {
+      season,
+      format
+    }
+  }
+}
+
+impl MetadataProvider for FileProvider {
+  async fn series_season(&self, path: &str) -> Result<EpisodesDefinition, RenamerError> {
+    let path = Path::new(path);
+
+    let episodes_definition =
+      match read_metadata_file(path, self.format)? {
+        SeriesMetadataFile::Season(episodes_definition) => episodes_definition,
+        SeriesMetadataFile::AllSeasons(seasons_definition) => {
+          let season = self.season.ok_or_else(|| RenamerError::MissingSeasonForSeries(path.to_owned(), seasons_definition.season_numbers().iter().map(|s| s.to_string()).collect::<Vec<_>>().join(", ")))?;
+
+          seasons_definition
+            .season(season)
+            .cloned()
+            .ok_or_else(|| RenamerError::SeasonNotInMetadata(path.to_owned(), season))?
+        }
+      };
+
+    match episodes_definition.metadata.id_tags() {
+      Some(_) => Ok(episodes_definition),
+      None => Err(RenamerError::MissingMetadataId(path.to_owned(), episodes_definition.metadata.name))
+    }
+  }
+
+  async fn movie(&self, path: &str) -> Result<MovieMetadata, RenamerError> {
+    let path = Path::new(path);
+
+    match MetadataFormat::for_path(path, self.format) {
+      MetadataFormat::Csv => Err(RenamerError::MetadataFormatNotSupported(MetadataFormat::Csv.as_str().to_owned(), "movies".to_owned())),
+      _ => {
+        let movie_metadata: MovieMetadata = read_metadata_file(path, self.format)?;
+
+        match movie_metadata.movies().into_iter().find(|movie| movie.id_tags().is_none()) {
+          Some(movie) => Err(RenamerError::MissingMetadataId(path.to_owned(), movie.name().to_owned())),
+          None => Ok(movie_metadata)
+        }
+      }
+    }
+  }
+
+  async fn season_sources(&self, _path: &str) -> Result<Vec<String>, RenamerError> {
+    Err(RenamerError::SeasonsNotSupported("file".to_owned()))
+  }
+
+  async fn search(&self, _query: &SearchQuery) -> Result<Vec<SearchResult>, RenamerError> {
+    Err(RenamerError::SearchNotSupported("file".to_owned()))
+  }
+
+  async fn series_artwork(&self, _path: &str) -> Result<Artwork, RenamerError> {
+    Err(RenamerError::ArtworkNotSupported("file".to_owned()))
+  }
+
+  async fn movie_artwork(&self, _path: &str) -> Result<Artwork, RenamerError> {
+    Err(RenamerError::ArtworkNotSupported("file".to_owned()))
+  }
+
+  fn http_client(&self) -> Option<&HttpClient> {
+    None
+  }
+}
+
+fn read_metadata_file<T: MetadataFile>(path: &Path, format: Option<MetadataFormat>) -> Result<T, RenamerError> {
+  if path.exists() {
+    read_input_from_file(path, format)
+  } else {
+    Err(RenamerError::MetadataDirectoryDoesNotExist(path.to_owned()))
+  }
+}
+
+pub fn read_input_from_file<P: AsRef<Path>, R: MetadataFile>(path: P, format: Option<MetadataFormat>) -> Result<R, RenamerError> {
+  let path = path.as_ref();
+  let format = MetadataFormat::for_path(path, format);
+
+  let content =
+    fs::read_to_string(path)
+      .map_err(|e| RenamerError::CouldNotAccessMetadataFile(path.to_string_lossy().to_string(), e.to_string()))?;
+
+  R::decode(format, &content)
+    .map_err(|e| RenamerError::CouldNotDecodeMetadataFile(path.to_owned(), format.as_str().to_owned(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use pretty_assertions::assert_eq;
+
+    const ALL_SEASONS: &str = r#"{
+      "seasons": [
+        {
+          "metadata": { "name":"Thundercats", "tvdb_id":"70355", "season_number":"1" },
+          "episodes": [ { "number":"S01E01", "name":"Exodus"} ]
+        },
+        {
+          "metadata": { "name":"Thundercats", "tvdb_id":"70355", "season_number":"2" },
+          "episodes": [ { "number":"S02E01", "name":"Mumm-Ra Lives!"} ]
+        }
+      ]
+    }"#;
+
+    #[tokio::test]
+    async fn test_season_from_all_seasons() {
+      let dir = tempdir().unwrap();
+      let path = dir.path().join("thundercats.json");
+      fs::write(&path, ALL_SEASONS).unwrap();
+      let path = path.to_string_lossy();
+
+      let season = FileProvider::new(Some(2), None).series_season(&path).await.unwrap();
+      assert_eq!(season.metadata.season_number.value(), 2);
+
+      let missing_season = FileProvider::new(None, None).series_season(&path).await;
+      assert!(matches!(missing_season, Err(RenamerError::MissingSeasonForSeries(_, seasons)) if seasons == "1, 2"));
+
+      let unknown_season = FileProvider::new(Some(3), None).series_season(&path).await;
+      assert!(matches!(unknown_season, Err(RenamerError::SeasonNotInMetadata(_, 3))))
+    }
+
+    #[tokio::test]
+    async fn test_season_from_yaml() {
+      let dir = tempdir().unwrap();
+      let path = dir.path().join("thundercats.yml");
+      fs::write(&path, "metadata:\n  name: Thundercats\n  tvdb_id: '70355'\n  season_number: '1'\nepisodes:\n  - number: S01E01\n    name: Exodus\n").unwrap();
+
+      let season = FileProvider::new(None, None).series_season(&path.to_string_lossy()).await.unwrap();
+      assert_eq!(season.episodes[0].name, "Exodus")
+    }
+
+    #[tokio::test]
+    async fn test_metadata_without_ids() {
+      let dir = tempdir().unwrap();
+      let series_path = dir.path().join("thundercats.json");
+      fs::write(&series_path, r#"{ "metadata": { "name":"Thundercats", "season_number":"1" }, "episodes": [] }"#).unwrap();
+
+      let season = FileProvider::new(None, None).series_season(&series_path.to_string_lossy()).await;
+      assert!(matches!(season, Err(RenamerError::MissingMetadataId(_, name)) if name == "Thundercats"));
+
+      let movie_path = dir.path().join("box-set.json");
+      fs::write(&movie_path, r#"{ "movies": [ { "disc":"disc1", "name":"Back to the Future", "tvdb_id":"296" }, { "disc":"disc2", "name":"Back to the Future Part II" } ] }"#).unwrap();
+
+      let movie = FileProvider::new(None, None).movie(&movie_path.to_string_lossy()).await;
+      assert!(matches!(movie, Err(RenamerError::MissingMetadataId(_, name)) if name == "Back to the Future Part II"))
+    }
+
+    #[tokio::test]
+    async fn test_movie_from_csv() {
+      let dir = tempdir().unwrap();
+      let path = dir.path().join("movie.txt");
+      fs::write(&path, "number,name\n").unwrap();
+
+      let movie = FileProvider::new(None, Some(MetadataFormat::Csv)).movie(&path.to_string_lossy()).await;
+      assert!(matches!(movie, Err(RenamerError::MetadataFormatNotSupported(..))))
+    }
+}
@@ -0,0 +1,69 @@
+use std::fs;
+use crate::html_scraper::get_movie_definition;
+use crate::http_client::HttpClient;
+use crate::models::*;
+use super::tvdb_scrape::scrape_series;
+use super::{MetadataProvider, SearchQuery, SearchResult};
+
+/// Scrapes a TVDB page that has been saved to a local file
+pub struct HtmlFileProvider;
+
+impl MetadataProvider for HtmlFileProvider {
+  async fn series_season(&self, path: &str) -> Result<EpisodesDefinition, RenamerError> {
+    let page_content = read_html_file(path)?;
+    scrape_series(path, &page_content)
+  }
+
+  async fn movie(&self, path: &str) -> Result<MovieMetadata, RenamerError> {
+    let page_content = read_html_file(path)?;
+    get_movie_definition(path, &page_content).map(MovieMetadata::Single)
+  }
+
+  async fn season_sources(&self, _path: &str) -> Result<Vec<String>, RenamerError> {
+    Err(RenamerError::SeasonsNotSupported("html-file".to_owned()))
+  }
+
+  async fn search(&self, _query: &SearchQuery) -> Result<Vec<SearchResult>, RenamerError> {
+    Err(RenamerError::SearchNotSupported("html-file".to_owned()))
+  }
+
+  async fn series_artwork(&self, _path: &str) -> Result<Artwork, RenamerError> {
+    Err(RenamerError::ArtworkNotSupported("html-file".to_owned()))
+  }
+
+  async fn movie_artwork(&self, _path: &str) -> Result<Artwork, RenamerError> {
+    Err(RenamerError::ArtworkNotSupported("html-file".to_owned()))
+  }
+
+  fn http_client(&self) -> Option<&HttpClient> {
+    None
+  }
+}
+
+fn read_html_file(path: &str) -> Result<String, RenamerError> {
+  fs::read_to_string(path)
+    .map_err(|e| RenamerError::CouldNotAccessMetadataFile(path.to_owned(), e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn test_movie_from_html_file() {
+      let provider = HtmlFileProvider;
+      let result = provider.movie("tests/fixtures/html/star-wars-rise-of-skywalker.html").await.unwrap();
+
+      let expected = MovieMetadata::Single(MovieDefinition::new("Star Wars: The Rise of Skywalker".to_owned(), "12879".to_owned()));
+      assert_eq!(result, expected)
+    }
+
+    #[tokio::test]
+    async fn test_missing_html_file() {
+      let provider = HtmlFileProvider;
+      let result = provider.series_season("tests/fixtures/html/does-not-exist.html").await;
+
+      assert!(matches!(result, Err(RenamerError::CouldNotAccessMetadataFile(path, _)) if path == "tests/fixtures/html/does-not-exist.html"))
+    }
+}
@@ -0,0 +1,49 @@
+use crate::http_client::HttpClient;
+use crate::models::*;
+use super::{MetadataProvider, SearchQuery, SearchResult};
+
+/// Serves fixed metadata, for testing workflows without a network or files
+#[derive(Default)]
+pub struct InMemoryProvider {
+  pub series: Option<EpisodesDefinition>,
+  pub seasons: Vec<EpisodesDefinition>,
+  pub movie: Option<MovieMetadata>,
+  pub search_results: Vec<SearchResult>,
+  pub artwork: Artwork,
+}
+
+impl MetadataProvider for InMemoryProvider {
+  // Sources are season numbers when there are several seasons
+  async fn series_season(&self, source: &str) -> Result<EpisodesDefinition, RenamerError> {
+    self
+      .seasons
+      .iter()
+      .find(|s| s.metadata.season_number.to_string() == source)
+      .or(self.series.as_ref())
+      .cloned().ok_or_else(|| RenamerError::CouldNotAccessMetadataURL(source.to_owned(), "No series".to_owned()))
+  }
+
+  async fn movie(&self, source: &str) -> Result<MovieMetadata, RenamerError> {
+    self.movie.clone().ok_or_else(|| RenamerError::CouldNotAccessMetadataURL(source.to_owned(), "No movie".to_owned()))
+  }
+
+  async fn season_sources(&self, _source: &str) -> Result<Vec<String>, RenamerError> {
+    Ok(self.seasons.iter().map(|s| s.metadata.season_number.to_string()).collect())
+  }
+
+  async fn search(&self, _query: &SearchQuery) -> Result<Vec<SearchResult>, RenamerError> {
+    Ok(self.search_results.clone())
+  }
+
+  async fn series_artwork(&self, _source: &str) -> Result<Artwork, RenamerError> {
+    Ok(self.artwork.clone())
+  }
+
+  async fn movie_artwork(&self, _source: &str) -> Result<Artwork, RenamerError> {
+    Ok(self.artwork.clone())
+  }
+
+  fn http_client(&self) -> Option<&HttpClient> {
+    None
+  }
+}
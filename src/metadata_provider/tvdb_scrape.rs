@@ -0,0 +1,85 @@
+use console::Style;
+use crate::html_scraper::{get_movie_definition, get_season_numbers, get_series_metadata};
+use crate::http_client::HttpClient;
+use crate::metadata_downloader::download_metadata;
+use crate::models::*;
+use crate::tvdb_api::{SeasonType, TvdbSeasonUrl, TvdbSeriesUrl, TVDB_WEBSITE};
+use super::{MetadataProvider, SearchQuery, SearchResult};
+
+/// Scrapes the TVDB website
+pub struct TvdbScrapeProvider {
+  http: HttpClient,
+}
+
+impl TvdbScrapeProvider {
+  pub fn new(http: HttpClient) -> Self {
+    Self {
+      http
+    }
+  }
+}
+
+impl MetadataProvider for TvdbScrapeProvider {
+  async fn series_season(&self, url: &str) -> Result<EpisodesDefinition, RenamerError> {
+    let page_content = download_metadata(&self.http, url).await?;
+    let mut episodes_definition = scrape_series(url, &page_content)?;
+    let url_order = TvdbSeasonUrl::parse(url).ok().map(|season_url| season_url.season_type.into());
+    episodes_definition.metadata.order = url_order.or(episodes_definition.metadata.order);
+
+    Ok(episodes_definition)
+  }
+
+  async fn movie(&self, url: &str) -> Result<MovieMetadata, RenamerError> {
+    let page_content = download_metadata(&self.http, url).await?;
+    get_movie_definition(url, &page_content).map(MovieMetadata::Single)
+  }
+
+  // Season urls use the official order. The order of each season can then be chosen with --order.
+  async fn season_sources(&self, url: &str) -> Result<Vec<String>, RenamerError> {
+    let slug =
+      match TvdbSeriesUrl::parse(url)? {
+        TvdbSeriesUrl::Slug(slug) => slug,
+        TvdbSeriesUrl::Id(_) => return Err(RenamerError::InvalidTvdbUrl(url.to_owned())),
+      };
+
+    let series_url = format!("{TVDB_WEBSITE}/series/{slug}");
+    let page_content = download_metadata(&self.http, &series_url).await?;
+    let season_numbers = get_season_numbers(&series_url, &page_content)?;
+
+    Ok(
+      season_numbers
+        .into_iter()
+        .map(|season| TvdbSeasonUrl { slug: slug.clone(), season_type: SeasonType::Official, season }.to_url())
+        .collect()
+    )
+  }
+
+  // TVDB search results are rendered by JavaScript, so there is nothing to scrape
+  async fn search(&self, _query: &SearchQuery) -> Result<Vec<SearchResult>, RenamerError> {
+    Err(RenamerError::SearchNotSupported("tvdb-scrape".to_owned()))
+  }
+
+  async fn series_artwork(&self, _url: &str) -> Result<Artwork, RenamerError> {
+    Err(RenamerError::ArtworkNotSupported("tvdb-scrape".to_owned()))
+  }
+
+  async fn movie_artwork(&self, _url: &str) -> Result<Artwork, RenamerError> {
+    Err(RenamerError::ArtworkNotSupported("tvdb-scrape".to_owned()))
+  }
+
+  fn http_client(&self) -> Option<&HttpClient> {
+    Some(&self.http)
+  }
+}
+
+/// Scrapes a season page, reporting any episode rows that were skipped
+pub(super) fn scrape_series(source: &str, html: &str) -> Result<EpisodesDefinition, RenamerError> {
+  let scraped = get_series_metadata(source, html)?;
+
+  let yellow = Style::new().yellow();
+  for warning in scraped.warnings {
+    eprintln!("{} {}", yellow.apply_to("Warning:"), warning)
+  }
+
+  Ok(scraped.value)
+}
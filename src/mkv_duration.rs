@@ -0,0 +1,166 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+// Matroska element ids. See https://www.matroska.org/technical/elements.html
+const EBML_HEADER_ID: u32 = 0x1A45DFA3;
+const SEGMENT_ID: u32 = 0x18538067;
+const INFO_ID: u32 = 0x1549A966;
+const CLUSTER_ID: u32 = 0x1F43B675;
+const TIMESTAMP_SCALE_ID: u32 = 0x2AD7B1;
+const DURATION_ID: u32 = 0x4489;
+
+// Timestamps are in milliseconds unless the file says otherwise
+const DEFAULT_TIMESTAMP_SCALE_NANOS: u64 = 1_000_000;
+
+/// Reads the duration of an MKV file from its segment info, without reading the rest of the file.
+/// Returns None if the file can't be read or has no duration.
+pub fn read_duration(path: &Path) -> Option<Duration> {
+  let file = File::open(path).ok()?;
+  parse_duration(&mut BufReader::new(file))
+}
+
+fn parse_duration<R: Read + Seek>(reader: &mut R) -> Option<Duration> {
+  let (id, size) = read_element_header(reader)?;
+  if id != EBML_HEADER_ID {
+    return None
+  }
+  skip(reader, size?)?;
+
+  let (id, _) = read_element_header(reader)?;
+  if id != SEGMENT_ID {
+    return None
+  }
+
+  // The segment info comes before the clusters that hold the video
+  loop {
+    let (id, size) = read_element_header(reader)?;
+    match id {
+      INFO_ID => return parse_info(reader, size?),
+      CLUSTER_ID => return None,
+      _ => skip(reader, size?)?
+    }
+  }
+}
+
+fn parse_info<R: Read + Seek>(reader: &mut R, info_size: u64) -> Option<Duration> {
+  let mut timestamp_scale = DEFAULT_TIMESTAMP_SCALE_NANOS;
+  let mut duration = None;
+  let end = reader.stream_position().ok()? + info_size;
+
+  while reader.stream_position().ok()? < end {
+    let (id, size) = read_element_header(reader)?;
+    let size = size?;
+
+    match id {
+      TIMESTAMP_SCALE_ID => timestamp_scale = read_uint(reader, size)?,
+      DURATION_ID => duration = Some(read_float(reader, size)?),
+      _ => skip(reader, size)?
+    }
+  }
+
+  duration
+    .filter(|ticks| ticks.is_finite() && *ticks > 0.0)
+    .map(|ticks| Duration::from_nanos((ticks * timestamp_scale as f64) as u64))
+}
+
+// Returns the element id and its size, which is None when unknown
+fn read_element_header<R: Read>(reader: &mut R) -> Option<(u32, Option<u64>)> {
+  let (id, _) = read_vint(reader, false)?;
+  let (size, unknown) = read_vint(reader, true)?;
+
+  Some((id as u32, (!unknown).then_some(size)))
+}
+
+// Variable length integers start with a marker bit, whose position gives the length in bytes.
+// Ids keep the marker bit and sizes drop it. A size with every value bit set is unknown.
+fn read_vint<R: Read>(reader: &mut R, strip_marker: bool) -> Option<(u64, bool)> {
+  let first = read_bytes(reader, 1)?[0];
+  let length = first.leading_zeros() as usize + 1;
+  if length > 8 {
+    return None
+  }
+
+  let marker = if strip_marker { 0x80u8 >> (length - 1) } else { 0 };
+  let value =
+    read_bytes(reader, length - 1)?
+      .iter()
+      .fold(u64::from(first & !marker), |value, byte| (value << 8) | u64::from(*byte));
+
+  let is_unknown = strip_marker && value == (1u64 << (7 * length)) - 1;
+
+  Some((value, is_unknown))
+}
+
+fn read_uint<R: Read>(reader: &mut R, size: u64) -> Option<u64> {
+  if size > 8 {
+    return None
+  }
+
+  Some(read_bytes(reader, size as usize)?.iter().fold(0, |value, byte| (value << 8) | u64::from(*byte)))
+}
+
+// Floats are 4 or 8 bytes. The size is checked before reading, as a corrupt file can declare any size.
+fn read_float<R: Read>(reader: &mut R, size: u64) -> Option<f64> {
+  match size {
+    4 => Some(f64::from(f32::from_be_bytes(read_bytes(reader, 4)?.try_into().ok()?))),
+    8 => Some(f64::from_be_bytes(read_bytes(reader, 8)?.try_into().ok()?)),
+    _ => None
+  }
+}
+
+fn read_bytes<R: Read>(reader: &mut R, count: usize) -> Option<Vec<u8>> {
+  let mut bytes = vec![0; count];
+  reader.read_exact(&mut bytes).ok()?;
+  Some(bytes)
+}
+
+fn skip<R: Seek>(reader: &mut R, size: u64) -> Option<()> {
+  reader.seek(SeekFrom::Current(i64::try_from(size).ok()?)).ok().map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use pretty_assertions::assert_eq;
+
+    fn element(id: &[u8], content: &[u8]) -> Vec<u8> {
+      [id, &[0x80 | content.len() as u8], content].concat()
+    }
+
+    fn mkv(info: &[u8]) -> Vec<u8> {
+      let header = element(&[0x1A, 0x45, 0xDF, 0xA3], &element(&[0x42, 0x82], b"matroska"));
+      let void = element(&[0xEC], &[0; 4]);
+      let info = element(&[0x15, 0x49, 0xA9, 0x66], info);
+
+      // The segment has an unknown size, as when written by a live muxer
+      [header, vec![0x18, 0x53, 0x80, 0x67, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF], void, info].concat()
+    }
+
+    #[test]
+    fn test_duration_with_default_scale() {
+      // 22 minutes in milliseconds
+      let info = element(&[0x44, 0x89], &1_320_000f64.to_be_bytes());
+      assert_eq!(parse_duration(&mut Cursor::new(mkv(&info))), Some(Duration::from_secs(22 * 60)))
+    }
+
+    #[test]
+    fn test_duration_with_scale() {
+      let info = [element(&[0x2A, 0xD7, 0xB1], &[0x3B, 0x9A, 0xCA, 0x00]), element(&[0x44, 0x89], &90f32.to_be_bytes())].concat();
+      assert_eq!(parse_duration(&mut Cursor::new(mkv(&info))), Some(Duration::from_secs(90)))
+    }
+
+    #[test]
+    fn test_oversized_duration() {
+      // A duration element that declares a size of 2^40 bytes, with no content
+      let info = [0x44, 0x89, 0x02, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00];
+      assert_eq!(parse_duration(&mut Cursor::new(mkv(&info))), None)
+    }
+
+    #[test]
+    fn test_not_mkv() {
+      assert_eq!(parse_duration(&mut Cursor::new(b"RIFF\x00\x00\x00\x00AVI ".to_vec())), None)
+    }
+}
@@ -5,23 +5,41 @@ mod dirs;
 mod series;
 mod movie;
 mod errors;
+mod metadata_format;
+mod numbers;
+mod air_date;
+mod artwork;
+mod disc_info;
 pub use dirs::*;
 pub use series::*;
 pub use movie::*;
 pub use errors::*;
+pub use metadata_format::*;
+pub use numbers::*;
+pub use air_date::*;
+pub use artwork::*;
+pub use disc_info::*;
 
 pub type R = Result<(), RenamerError>;
 pub type ROutput = Result<Output, RenamerError>;
 
 pub enum Output {
   Success,
-  UserCanceled
+  UserCanceled,
+  ExportedToStdout,
+  Valid,
 }
 
 #[derive(Debug)]
 pub struct Rename {
   pub from_file_name: PathBuf,
   pub to_file_name: PathBuf,
+
+  /// Shown below the rename when confirming, such as the air date of an episode
+  pub details: Vec<String>,
+
+  /// Problems that may mean the file was matched to the wrong metadata
+  pub warnings: Vec<String>,
 }
 
 impl Rename {
@@ -29,6 +47,16 @@ impl Rename {
     Self {
       from_file_name: from,
       to_file_name: to,
+      details: vec![],
+      warnings: vec![],
+    }
+  }
+
+  pub fn with_details(self, details: Vec<String>, warnings: Vec<String>) -> Self {
+    Self {
+      details,
+      warnings,
+      ..self
     }
   }
 }
@@ -37,7 +65,10 @@ impl Rename {
 pub struct FileNameAndExt {
   pub path: PathBuf,
   pub file_name: String,
-  pub ext: String
+  pub ext: String,
+
+  /// What MakeMKV listed for this title, when the disc directory has an info log
+  pub title_info: Option<TitleInfo>
 }
 
 impl FileNameAndExt {
@@ -45,9 +76,22 @@ impl FileNameAndExt {
     Self {
       path: path.to_path_buf(),
       file_name: file_name.to_string_lossy().to_string(),
-      ext: ext.to_string_lossy().to_string()
+      ext: ext.to_string_lossy().to_string(),
+      title_info: None
+    }
+  }
+
+  pub fn with_title_info(self, title_info: Option<TitleInfo>) -> Self {
+    Self {
+      title_info,
+      ..self
     }
   }
+
+  /// Whether a title given in a metadata file is this file, by its name or the MakeMKV title it was ripped from
+  pub fn is_title(&self, title: &str) -> bool {
+    self.file_name == title || self.title_info.as_ref().is_some_and(|title_info| title_info.is_named(title))
+  }
 }
 
 impl PartialOrd for FileNameAndExt {
@@ -61,8 +105,34 @@ pub enum RenamesResult {
   Wrong
 }
 
+/// The Plex `{source-id}` and Jellyfin `[sourceid-id]` tags for a series or movie.
+/// TVDB ids are preferred when both TVDB and TMDB ids are known.
+pub fn id_tags(tvdb_id: Option<&str>, tmdb_id: Option<&str>) -> Option<String> {
+  match (tvdb_id, tmdb_id) {
+    (Some(tvdb_id), _) => Some(format!("{{tvdb-{tvdb_id}}} [tvdbid-{tvdb_id}]")),
+    (None, Some(tmdb_id)) => Some(format!("{{tmdb-{tmdb_id}}} [tmdbid-{tmdb_id}]")),
+    (None, None) => None
+  }
+}
+
+/// The name followed by the year, as Plex and Jellyfin expect. Example: Doctor Who (2005)
+/// Names that already end with the year, as some TVDB names do, are left as they are.
+pub fn name_with_year(name: &str, year: Option<u16>) -> String {
+  match year {
+    Some(year) if !name.ends_with(&format!("({year})")) => format!("{name} ({year})"),
+    _ => name.to_owned()
+  }
+}
+
+/// The name with its path separators replaced, so it stays a single file name within its directory.
+/// Example: Exodus / Part 1 becomes Exodus - Part 1
+pub fn file_name_safe(name: &str) -> String {
+  name.replace(['/', '\\'], "-")
+}
+
 pub enum ConfigMetadataInputType {
   Url(String),
   File(String),
+  HtmlFile(String),
   Invalid
 }
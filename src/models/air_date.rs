@@ -0,0 +1,117 @@
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+const MONTHS: [&str; 12] = ["January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December"];
+
+/// The date an episode first aired.
+///
+/// Parsed from 2003-04-12 or April 12, 2003 (as shown on TVDB), and always written as 2003-04-12.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct AirDate {
+  year: u32,
+  month: u32,
+  day: u32,
+}
+
+impl AirDate {
+  pub fn new(year: u32, month: u32, day: u32) -> Result<Self, String> {
+    let days_in_month =
+      match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return Err(format!("Air date: month {month} is not between 1 and 12"))
+      };
+
+    if !(1000..=9999).contains(&year) {
+      Err(format!("Air date: year {year} is not 4 digits"))
+    } else if day == 0 || day > days_in_month {
+      Err(format!("Air date: day {day} is not in {}", MONTHS[month as usize - 1]))
+    } else {
+      Ok(AirDate { year, month, day })
+    }
+  }
+
+  pub fn year(&self) -> u32 {
+    self.year
+  }
+}
+
+fn is_leap_year(year: u32) -> bool {
+  (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+impl fmt::Display for AirDate {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+  }
+}
+
+impl FromStr for AirDate {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    let invalid_date = || format!("Air date: '{value}' is not in 2003-04-12 or April 12, 2003 format");
+    let value = value.trim();
+
+    let (year, month, day) =
+      if let [year, month, day] = value.split('-').collect::<Vec<_>>().as_slice() {
+        (year.to_string(), month.to_string(), day.to_string())
+      } else if let Some((month_and_day, year)) = value.split_once(',') {
+        // Given: April 12, 2003, returns: (2003, 4, 12)
+        let (month_name, day) = month_and_day.trim().split_once(' ').ok_or_else(invalid_date)?;
+        let month =
+          MONTHS
+            .iter()
+            .position(|m| m.eq_ignore_ascii_case(month_name))
+            .ok_or_else(invalid_date)?;
+
+        (year.trim().to_owned(), (month + 1).to_string(), day.trim().to_owned())
+      } else {
+        return Err(invalid_date())
+      };
+
+    let number = |value: String| value.parse::<u32>().map_err(|_| invalid_date());
+
+    AirDate::new(number(year)?, number(month)?, number(day)?)
+  }
+}
+
+impl From<AirDate> for String {
+  fn from(air_date: AirDate) -> Self {
+    air_date.to_string()
+  }
+}
+
+impl TryFrom<String> for AirDate {
+  type Error = String;
+
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    value.parse()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_air_date() {
+      let expected = AirDate::new(2003, 4, 12).unwrap();
+
+      assert_eq!("2003-04-12".parse::<AirDate>(), Ok(expected));
+      assert_eq!("April 12, 2003".parse::<AirDate>(), Ok(expected));
+      assert_eq!("2004-02-29".parse::<AirDate>(), Ok(AirDate::new(2004, 2, 29).unwrap()));
+      assert_eq!("2003-02-29".parse::<AirDate>(), Err("Air date: day 29 is not in February".to_owned()));
+      assert_eq!("12/04/2003".parse::<AirDate>(), Err("Air date: '12/04/2003' is not in 2003-04-12 or April 12, 2003 format".to_owned()))
+    }
+
+    #[test]
+    fn test_format_air_date() {
+      assert_eq!(AirDate::new(1985, 1, 3).unwrap().to_string(), "1985-01-03")
+    }
+}
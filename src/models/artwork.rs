@@ -0,0 +1,22 @@
+/// The image urls for a series season or movie. Each is None when the provider has no image of that kind.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Artwork {
+  pub poster: Option<String>,
+  pub fanart: Option<String>,
+
+  /// Only known for series
+  pub season_poster: Option<String>,
+}
+
+impl Artwork {
+  /// Providers list missing images as empty urls
+  pub fn without_empty(self) -> Self {
+    let non_empty = |url: Option<String>| url.filter(|url| !url.is_empty());
+
+    Self {
+      poster: non_empty(self.poster),
+      fanart: non_empty(self.fanart),
+      season_poster: non_empty(self.season_poster),
+    }
+  }
+}
@@ -34,6 +34,18 @@ impl RipsSessionNumberDir {
 #[derive(Debug)]
 pub struct RipsSessionRenamesDir(pub PathBuf);
 
+impl RipsSessionRenamesDir {
+  /// sessionX/renames/Featurettes
+  pub fn season_extras_dir(&self) -> PathBuf {
+    self.0.join("Featurettes")
+  }
+
+  /// sessionX/renames/Series Featurettes
+  pub fn series_extras_dir(&self) -> PathBuf {
+    self.0.join("Series Featurettes")
+  }
+}
+
 impl AsRef<Path> for RipsSessionRenamesDir {
   fn as_ref(&self) -> &Path {
     self.0.as_path()
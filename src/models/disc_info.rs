@@ -0,0 +1,223 @@
+use std::time::Duration;
+
+/// The info log saved in each disc directory, from: makemkvcon -r info disc:0 > makemkv-info.txt
+pub const DISC_INFO_FILE: &str = "makemkv-info.txt";
+
+// Attribute ids of CINFO and TINFO lines. See apdefs.h in the MakeMKV sources
+const NAME_ID: u32 = 2;
+const CHAPTER_COUNT_ID: u32 = 8;
+const DURATION_ID: u32 = 9;
+const SIZE_BYTES_ID: u32 = 11;
+const SOURCE_FILE_NAME_ID: u32 = 16;
+const SEGMENTS_MAP_ID: u32 = 26;
+const OUTPUT_FILE_NAME_ID: u32 = 27;
+
+/// What MakeMKV found on a disc, as written in robot mode (-r) by makemkvcon info.
+/// Lines that are not understood, such as MSG and SINFO lines, are ignored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiscInfo {
+  pub name: Option<String>,
+  pub titles: Vec<TitleInfo>,
+}
+
+/// A title on a disc, which MakeMKV rips to its own file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TitleInfo {
+  /// The position of the title on the disc, starting from 0
+  pub index: u32,
+  pub disc_name: Option<String>,
+  pub name: Option<String>,
+  pub duration: Option<Duration>,
+  pub chapters: Option<u32>,
+  pub size_bytes: Option<u64>,
+
+  /// The playlist or file the title was read from. Eg. 00800.mpls
+  pub source_file_name: Option<String>,
+
+  /// The segments the title plays, in order. Eg. 1,2,3
+  pub segments_map: Option<String>,
+
+  /// The name of the ripped file. Eg. title_t00.mkv
+  pub output_file_name: Option<String>,
+}
+
+impl DiscInfo {
+  pub fn parse(content: &str) -> Self {
+    let mut disc_info = DiscInfo::default();
+
+    for line in content.lines() {
+      let Some((kind, fields)) = line.trim().split_once(':') else { continue };
+      let fields = split_fields(fields);
+
+      match (kind, fields.as_slice()) {
+        ("CINFO", [id, _, value]) if id.parse() == Ok(NAME_ID) => disc_info.name = non_empty(value),
+        ("TINFO", [index, id, _, value]) => {
+          let (Ok(index), Ok(id)) = (index.parse::<u32>(), id.parse::<u32>()) else { continue };
+          disc_info.title_mut(index).set(id, value)
+        },
+        _ => ()
+      }
+    }
+
+    // The disc name is listed once, before the titles
+    for title in &mut disc_info.titles {
+      title.disc_name = disc_info.name.clone()
+    }
+
+    disc_info
+  }
+
+  /// The title that was ripped to the file
+  pub fn title(&self, file_name: &str) -> Option<&TitleInfo> {
+    self
+      .titles
+      .iter()
+      .find(|title| title.output_file_name.as_deref() == Some(file_name))
+  }
+
+  fn title_mut(&mut self, index: u32) -> &mut TitleInfo {
+    let position =
+      match self.titles.iter().position(|title| title.index == index) {
+        Some(position) => position,
+        None => {
+          self.titles.push(TitleInfo { index, ..TitleInfo::default() });
+          self.titles.len() - 1
+        }
+      };
+
+    &mut self.titles[position]
+  }
+}
+
+impl TitleInfo {
+  fn set(&mut self, id: u32, value: &str) {
+    match id {
+      NAME_ID => self.name = non_empty(value),
+      CHAPTER_COUNT_ID => self.chapters = value.parse().ok(),
+      DURATION_ID => self.duration = parse_duration(value),
+      SIZE_BYTES_ID => self.size_bytes = value.parse().ok(),
+      SOURCE_FILE_NAME_ID => self.source_file_name = non_empty(value),
+      SEGMENTS_MAP_ID => self.segments_map = non_empty(value),
+      OUTPUT_FILE_NAME_ID => self.output_file_name = non_empty(value),
+      _ => ()
+    }
+  }
+
+  /// Whether a title given in a metadata file is this one, by its ripped file, the playlist it was read from or its name
+  pub fn is_named(&self, title: &str) -> bool {
+    [&self.output_file_name, &self.source_file_name, &self.name]
+      .into_iter()
+      .any(|name| name.as_deref() == Some(title))
+  }
+
+  /// A single line describing the title, shown when confirming renames.
+  /// Example: Disc THUNDERCATS_S1_D1 title 3 'Exodus, Part 1' (00800.mpls): 0:22:14, 6 chapters, segments 1,2
+  pub fn summary(&self) -> String {
+    let disc_name = self.disc_name.as_ref().map(|disc_name| format!(" {disc_name}")).unwrap_or_default();
+    let name = self.name.as_ref().map(|name| format!(" '{name}'")).unwrap_or_default();
+    let source = self.source_file_name.as_ref().map(|source| format!(" ({source})")).unwrap_or_default();
+
+    let details =
+      [
+        self.duration.map(format_duration),
+        self.chapters.map(|chapters| format!("{chapters} chapters")),
+        self.segments_map.as_ref().map(|segments| format!("segments {segments}")),
+      ]
+      .into_iter()
+      .flatten()
+      .collect::<Vec<_>>()
+      .join(", ");
+
+    format!("Disc{disc_name} title {}{name}{source}: {details}", self.index)
+  }
+}
+
+// Fields are separated by commas, and text is quoted as it can contain commas. Eg. 0,2,0,"Exodus, Part 1"
+fn split_fields(fields: &str) -> Vec<String> {
+  let mut values = vec![];
+  let mut value = String::new();
+  let mut in_quotes = false;
+  let mut chars = fields.chars();
+
+  while let Some(c) = chars.next() {
+    match c {
+      '"' => in_quotes = !in_quotes,
+      '\\' if in_quotes => value.extend(chars.next()),
+      ',' if !in_quotes => values.push(std::mem::take(&mut value)),
+      c => value.push(c),
+    }
+  }
+
+  values.push(value);
+  values
+}
+
+fn non_empty(value: &str) -> Option<String> {
+  Some(value.trim().to_owned()).filter(|value| !value.is_empty())
+}
+
+// Given: 1:02:03, returns: 3723 seconds
+fn parse_duration(value: &str) -> Option<Duration> {
+  match value.split(':').map(|part| part.parse::<u64>().ok()).collect::<Option<Vec<_>>>()?.as_slice() {
+    [hours, minutes, seconds] => Some(Duration::from_secs(hours * 3600 + minutes * 60 + seconds)),
+    _ => None
+  }
+}
+
+fn format_duration(duration: Duration) -> String {
+  let seconds = duration.as_secs();
+  format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const INFO: &str = include_str!("../../tests/fixtures/makemkv/makemkv-info.txt");
+
+    #[test]
+    fn test_parse_disc_info() {
+      let disc_info = DiscInfo::parse(INFO);
+
+      assert_eq!(disc_info.name, Some("THUNDERCATS_S1_D1".to_owned()));
+      assert_eq!(disc_info.titles.len(), 3);
+
+      let expected =
+        TitleInfo {
+          index: 1,
+          disc_name: Some("THUNDERCATS_S1_D1".to_owned()),
+          name: Some("Exodus, Part 1".to_owned()),
+          duration: Some(Duration::from_secs(22 * 60 + 14)),
+          chapters: Some(6),
+          size_bytes: Some(1_234_567_890),
+          source_file_name: Some("00801.mpls".to_owned()),
+          segments_map: Some("801".to_owned()),
+          output_file_name: Some("title_t01.mkv".to_owned()),
+        };
+
+      assert_eq!(disc_info.title("title_t01.mkv"), Some(&expected));
+      assert_eq!(disc_info.title("title_t09.mkv"), None)
+    }
+
+    #[test]
+    fn test_title_summary() {
+      let disc_info = DiscInfo::parse(INFO);
+      let title = disc_info.title("title_t00.mkv").unwrap();
+
+      assert_eq!(title.summary(), "Disc THUNDERCATS_S1_D1 title 0 'Thundercats Season 1 Disc 1' (00800.mpls): 1:28:56, 24 chapters, segments 801,802,803,804");
+      assert!(title.is_named("00800.mpls"));
+      assert!(title.is_named("title_t00.mkv"));
+      assert!(title.is_named("Thundercats Season 1 Disc 1"));
+      assert!(!title.is_named("00801.mpls"));
+
+      let untitled = TitleInfo { index: 3, duration: Some(Duration::from_secs(60)), ..TitleInfo::default() };
+      assert_eq!(untitled.summary(), "Disc title 3: 0:01:00")
+    }
+
+    #[test]
+    fn test_split_fields() {
+      assert_eq!(split_fields(r#"0,2,0,"Exodus, \"Part\" 1""#), vec!["0", "2", "0", r#"Exodus, "Part" 1"#]);
+      assert_eq!(split_fields("1,0"), vec!["1", "0"])
+    }
+}
@@ -8,19 +8,59 @@ pub enum RenamerError {
   CouldNotAccessMetadataFile(String, String),
   CouldNotAccessMetadataURL(String, String),
   CouldNotDecodeMetadataBody(String, String),
-  CouldNotExportEpisodeMetadata(String, PathBuf, String),
+  MissingHtmlElement(String, String),
+  InvalidHtmlElement(String, String, String),
+  CouldNotScrapeEpisodeRow(String, usize, String),
+  MissingTvdbApiKey,
+  CouldNotLoginToTvdbApi(String, String),
+  InvalidTvdbUrl(String),
+  MissingTmdbApiKey,
+  InvalidTmdbUrl(String),
+  SearchNotSupported(String),
+  EpisodeOrderNotSupported(String, String),
+  LanguageNotSupported(String),
+  NoSearchProvider,
+  NoSearchResults(String),
+  InvalidSearchPick(u16, usize),
+  CouldNotCreateHttpClient(String),
+  NotInOfflineCache(String),
+  SeasonsNotSupported(String),
+  ArtworkNotSupported(String),
+  CouldNotDownloadArtwork(String, String),
+  MissingSeasonForSeries(PathBuf, String),
+  SeasonNotInMetadata(PathBuf, u32),
+  CouldNotExportSeriesMetadata(String, PathBuf, String),
+  CouldNotExportMovieMetadata(String, PathBuf, String),
+  ExportFileAlreadyExists(PathBuf),
+  CouldNotBackupExportFile(PathBuf, String),
   ProcessingDirectoryDoesNotExist(PathBuf),
   ProcessingDirAndMetadaPathDoesNotExit(PathBuf, PathBuf),
   MetadataDirectoryDoesNotExist(PathBuf),
-  CouldNotDecodeMetadataFileJson(PathBuf, String),
+  MissingMetadataId(PathBuf, String),
+  CouldNotDecodeMetadataFile(PathBuf, String, String),
+  MetadataFormatNotSupported(String, String),
+  InvalidMetadataFile(PathBuf, usize),
   NotEnoughMetadataForEpisodes(usize, usize),
+  CouldNotNumberEpisode(String, String),
+  NotEnoughMetadataForExtras(usize, usize),
+  NoRippedFileForExtra(String),
+  DuplicateExtraName(String),
   NoMovieDefinitionFound,
+  NoMovieDefinitionForRippedFile(PathBuf),
+  MultipleRippedFilesForMovie(String, usize),
+  NoRippedFileForMovie(String, String),
+  DuplicateBoxSetDisc(String),
+  DuplicateRenameTarget(PathBuf),
   NoFilesToRename,
   CouldNotCreatedSeriesDirectory(PathBuf, String),
+  CouldNotCreateRenamesDirectory(PathBuf, String),
   SeriesDirectoryAlreadyExists(PathBuf),
+  EncodedFileAlreadyExists(PathBuf),
   MovieDirectoryAlreadyExists(PathBuf),
   CouldNotOpenEncodesFile(PathBuf, String),
   CouldNotWriteEncodesFile(PathBuf, String),
+  CouldNotWriteNfoFile(PathBuf, String),
+  CouldNotWriteArtwork(PathBuf, String),
 }
 
 impl std::error::Error for RenamerError {}
@@ -33,18 +73,58 @@ impl fmt::Display for RenamerError {
         RenamerError::CouldNotAccessMetadataFile(file, message) => format!("Could not access metadata file: {file}, due to: {message}"),
         RenamerError::CouldNotAccessMetadataURL(url, message) => format!("Could not access metadata URL: {url}, due to: {message}"),
         RenamerError::CouldNotDecodeMetadataBody(url, message) => format!("Could not decode metadata body from URL: {url}, due to: {message}"),
-        RenamerError::CouldNotExportEpisodeMetadata(url, path, message) => format!("Could not export metadata from URL: {url} to file: {}, due to: {message}", path.to_string_lossy()),
+        RenamerError::MissingHtmlElement(url, element) => format!("Could not find {element} in HTML from URL: {url}. The page format may have changed"),
+        RenamerError::InvalidHtmlElement(url, element, value) => format!("Unexpected value: '{value}' for {element} in HTML from URL: {url}. The page format may have changed"),
+        RenamerError::CouldNotScrapeEpisodeRow(url, row, element) => format!("Skipped episode row {row} from URL: {url}, due to missing {element}"),
+        RenamerError::MissingTvdbApiKey => "A TVDB API key is required. Supply one with --tvdb-api-key or the TVDB_API_KEY environment variable".to_owned(),
+        RenamerError::CouldNotLoginToTvdbApi(url, message) => format!("Could not login to TVDB API: {url}, due to: {message}"),
+        RenamerError::InvalidTvdbUrl(url) => format!("Could not extract TVDB details from URL: {url}"),
+        RenamerError::MissingTmdbApiKey => "A TMDB API key is required. Supply one with --tmdb-api-key or the TMDB_API_KEY environment variable".to_owned(),
+        RenamerError::InvalidTmdbUrl(url) => format!("Could not extract TMDB details from URL: {url}"),
+        RenamerError::SearchNotSupported(provider) => format!("Searching is not supported by the {provider} provider"),
+        RenamerError::EpisodeOrderNotSupported(provider, order) => format!("The {order} episode order is not supported by the {provider} provider"),
+        RenamerError::LanguageNotSupported(provider) => format!("Names in another language are not supported by the {provider} provider. Use the tvdb-api or tmdb provider with --language"),
+        RenamerError::NoSearchProvider => "Searching requires the tvdb-api or tmdb provider. Supply --provider along with an API key".to_owned(),
+        RenamerError::NoSearchResults(name) => format!("No results were found for: {name}"),
+        RenamerError::InvalidSearchPick(pick, count) => format!("Could not pick result {pick}, as there are only {count} results"),
+        RenamerError::CouldNotCreateHttpClient(message) => format!("Could not create HTTP client, due to: {message}"),
+        RenamerError::NotInOfflineCache(url) => format!("Could not access metadata URL: {url} while offline, as it has not been cached"),
+        RenamerError::SeasonsNotSupported(provider) => format!("Listing the seasons of a series is not supported by the {provider} provider"),
+        RenamerError::ArtworkNotSupported(provider) => format!("Artwork is not supported by the {provider} provider. Use the tvdb-api or tmdb provider with --artwork"),
+        RenamerError::CouldNotDownloadArtwork(url, message) => format!("Could not download artwork: {url}, due to: {message}"),
+        RenamerError::MissingSeasonForSeries(path, seasons) => format!("Metadata file: {} contains seasons: {seasons}. Choose one with --season", path.to_string_lossy()),
+        RenamerError::SeasonNotInMetadata(path, season) => format!("Metadata file: {} does not contain season: {season}", path.to_string_lossy()),
+        RenamerError::CouldNotExportSeriesMetadata(source, path, message) => format!("Could not export series metadata from: {source} to file: {}, due to: {message}", path.to_string_lossy()),
+        RenamerError::CouldNotExportMovieMetadata(source, path, message) => format!("Could not export movie metadata from: {source} to file: {}, due to: {message}", path.to_string_lossy()),
+        RenamerError::ExportFileAlreadyExists(path) => format!("Export file: {} already exists. Use --force to overwrite it or --backup to keep a copy", path.to_string_lossy()),
+        RenamerError::CouldNotBackupExportFile(path, message) => format!("Could not backup existing export file: {}, due to: {message}", path.to_string_lossy()),
         RenamerError::ProcessingDirectoryDoesNotExist(path) => format!("Processing directory does not exist: {}", path.to_string_lossy()),
         RenamerError::ProcessingDirAndMetadaPathDoesNotExit(processing_dir, metadata_dir) => format!("Processing directory: {} and metadata path: {} does not exist", processing_dir.to_string_lossy(), metadata_dir.to_string_lossy()),
         RenamerError::MetadataDirectoryDoesNotExist(metadata_dir) => format!("Metadata path: {} does not exist", metadata_dir.to_string_lossy()),
-        RenamerError::CouldNotDecodeMetadataFileJson(path, message) => format!("Could not decode JSON from metadata file: {}, due to: {}", path.to_string_lossy(), message),
+        RenamerError::MissingMetadataId(path, name) => format!("Metadata file: {} has no tvdb_id or tmdb_id for: {name}. One is needed for the id tags in folder names", path.to_string_lossy()),
+        RenamerError::CouldNotDecodeMetadataFile(path, format, message) => format!("Could not decode {format} from metadata file: {}, due to: {}", path.to_string_lossy(), message),
+        RenamerError::MetadataFormatNotSupported(format, kind) => format!("{format} metadata files can't be used for {kind}"),
+        RenamerError::InvalidMetadataFile(path, count) => format!("Found {count} problem(s) in metadata file: {}", path.to_string_lossy()),
         RenamerError::NotEnoughMetadataForEpisodes(metadata, episodes) => format!("Not enough metadata episode names ({}) to match ripped files ({})", metadata, episodes),
+        RenamerError::CouldNotNumberEpisode(episode, message) => format!("Could not number episode: {episode} in the chosen style, as {message}"),
+        RenamerError::NotEnoughMetadataForExtras(metadata, extras) => format!("Not enough metadata extras names ({}) to match ripped extras files ({})", metadata, extras),
+        RenamerError::NoRippedFileForExtra(name) => format!("No ripped file matches the disc and title of extra: {name}"),
+        RenamerError::DuplicateExtraName(name) => format!("More than one extra would be named: {name}"),
         RenamerError::NoMovieDefinitionFound => "No metadata for movie found".to_owned(),
+        RenamerError::NoMovieDefinitionForRippedFile(path) => format!("No movie in the box set matches ripped file: {}", path.to_string_lossy()),
+        RenamerError::MultipleRippedFilesForMovie(movie, count) => format!("Found {count} ripped files for movie: {movie}. Add a title to the box set entry to choose one"),
+        RenamerError::NoRippedFileForMovie(movie, disc) => format!("No ripped file found in {disc} for movie: {movie}"),
+        RenamerError::DuplicateBoxSetDisc(disc) => format!("More than one box set movie is on {disc}. Add a different title to each of them"),
+        RenamerError::DuplicateRenameTarget(path) => format!("More than one file would be renamed to: {}", path.to_string_lossy()),
         RenamerError::NoFilesToRename => "No files found to rename".to_owned(),
         RenamerError::CouldNotCreatedSeriesDirectory(path, message) => format!("Could not create series directory: {}, due to: {}", path.to_string_lossy(), message),
+        RenamerError::CouldNotCreateRenamesDirectory(path, message) => format!("Could not create renames directory: {}, due to: {}", path.to_string_lossy(), message),
         RenamerError::CouldNotOpenEncodesFile(path, message) => format!("Could not open encodes.txt file for writing: {}, due to: {}", path.to_string_lossy(), message),
         RenamerError::CouldNotWriteEncodesFile(path, message) => format!("Could not write to encodes.txt file: {}, due to: {}", path.to_string_lossy(), message),
+        RenamerError::CouldNotWriteNfoFile(path, message) => format!("Could not write NFO file: {}, due to: {}", path.to_string_lossy(), message),
+        RenamerError::CouldNotWriteArtwork(path, message) => format!("Could not write artwork: {}, due to: {}", path.to_string_lossy(), message),
         RenamerError::SeriesDirectoryAlreadyExists(path) => format!("Series directory: {} already exists. Aborting.", path.to_string_lossy()),
+        RenamerError::EncodedFileAlreadyExists(path) => format!("Encoded file: {} already exists. Aborting.", path.to_string_lossy()),
         RenamerError::MovieDirectoryAlreadyExists(path) => format!("Movie directory: {} already exists. Aborting.", path.to_string_lossy()),
       };
 
@@ -0,0 +1,342 @@
+use std::path::Path;
+use clap::ValueEnum;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use super::{AirDate, EpisodeDefinition, EpisodeNumber, EpisodeOrder, EpisodesDefinition, ExtraDefinition, ExtrasLevel, SeasonNumber, SeriesMetaData};
+
+/// The formats metadata files can be read from and exported to
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum MetadataFormat {
+  Json,
+  Yaml,
+  Toml,
+
+  /// A single season of a series, as rows of number,name with an optional air_date
+  Csv,
+}
+
+impl MetadataFormat {
+  /// Uses the format if given, otherwise the format of the file extension. Defaults to JSON.
+  pub fn for_path(path: &Path, format: Option<MetadataFormat>) -> Self {
+    format
+      .or_else(|| Self::from_extension(path))
+      .unwrap_or(MetadataFormat::Json)
+  }
+
+  fn from_extension(path: &Path) -> Option<Self> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+
+    match extension.as_str() {
+      "json" => Some(MetadataFormat::Json),
+      "yaml" | "yml" => Some(MetadataFormat::Yaml),
+      "toml" => Some(MetadataFormat::Toml),
+      "csv" => Some(MetadataFormat::Csv),
+      _ => None
+    }
+  }
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      MetadataFormat::Json => "JSON",
+      MetadataFormat::Yaml => "YAML",
+      MetadataFormat::Toml => "TOML",
+      MetadataFormat::Csv => "CSV",
+    }
+  }
+
+  /// Whether the top level of the content has the field. Content that can't be parsed has no fields.
+  pub fn has_field(&self, content: &str, field: &str) -> bool {
+    match self {
+      MetadataFormat::Json => serde_json::from_str::<serde_json::Value>(content).is_ok_and(|value| value.get(field).is_some()),
+      MetadataFormat::Yaml => serde_yaml::from_str::<serde_yaml::Value>(content).is_ok_and(|value| value.get(field).is_some()),
+      MetadataFormat::Toml => toml::from_str::<toml::Table>(content).is_ok_and(|table| table.contains_key(field)),
+      MetadataFormat::Csv => false,
+    }
+  }
+
+  pub fn decode<R: DeserializeOwned>(&self, content: &str) -> Result<R, String> {
+    match self {
+      MetadataFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+      MetadataFormat::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+      MetadataFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+      MetadataFormat::Csv => {
+        // Decoded through JSON, so the result goes through the same checks as the other formats
+        read_csv(content)
+          .and_then(|episodes_definition| serde_json::to_value(episodes_definition).map_err(|e| e.to_string()))
+          .and_then(|value| serde_json::from_value(value).map_err(|e| e.to_string()))
+      }
+    }
+  }
+
+  /// Returns the path of each field in the content that is not part of the metadata
+  pub fn unknown_fields<R: DeserializeOwned>(&self, content: &str) -> Result<Vec<String>, String> {
+    let mut unknown_fields = vec![];
+    let callback = |path: serde_ignored::Path| unknown_fields.push(path.to_string());
+
+    match self {
+      MetadataFormat::Json => serde_ignored::deserialize::<_, _, R>(&mut serde_json::Deserializer::from_str(content), callback).map_err(|e| e.to_string())?,
+      MetadataFormat::Yaml => serde_ignored::deserialize::<_, _, R>(serde_yaml::Deserializer::from_str(content), callback).map_err(|e| e.to_string())?,
+      MetadataFormat::Toml => serde_ignored::deserialize::<_, _, R>(toml::Deserializer::new(content), callback).map_err(|e| e.to_string())?,
+      // Only the number and name columns are read
+      MetadataFormat::Csv => return Ok(vec![]),
+    };
+
+    Ok(unknown_fields)
+  }
+
+  pub fn encode<I: Serialize>(&self, metadata: &I) -> Result<String, String> {
+    match self {
+      MetadataFormat::Json => serde_json::to_string_pretty(metadata).map_err(|e| e.to_string()),
+      MetadataFormat::Yaml => serde_yaml::to_string(metadata).map_err(|e| e.to_string()),
+      MetadataFormat::Toml => toml::to_string_pretty(metadata).map_err(|e| e.to_string()),
+      MetadataFormat::Csv => {
+        serde_json::to_value(metadata)
+          .and_then(serde_json::from_value::<EpisodesDefinition>)
+          .map_err(|_| "CSV can only hold a single season of a series".to_owned())
+          .and_then(|episodes_definition| write_csv(&episodes_definition))
+      }
+    }
+  }
+}
+
+/// The content of a metadata file that can hold more than one kind of definition.
+/// The kind is chosen by a top level field and decoded on its own, so errors keep their field, line and column.
+pub trait MetadataFile: Sized {
+  fn decode(format: MetadataFormat, content: &str) -> Result<Self, String>;
+}
+
+// Rows with these numbers hold the series metadata and extras. All other rows are episodes.
+const SERIES_ROW: &str = "series";
+const SEASON_ROW: &str = "season";
+const TVDB_ID_ROW: &str = "tvdb_id";
+const TMDB_ID_ROW: &str = "tmdb_id";
+const IMDB_ID_ROW: &str = "imdb_id";
+const ORDER_ROW: &str = "order";
+const LANGUAGE_ROW: &str = "language";
+const YEAR_ROW: &str = "year";
+const EXTRA_ROW: &str = "extra";
+
+fn read_csv(content: &str) -> Result<EpisodesDefinition, String> {
+  let mut reader =
+    csv::ReaderBuilder::new()
+      .trim(csv::Trim::All)
+      .flexible(true)
+      .from_reader(content.as_bytes());
+
+  let mut name = None;
+  let mut season_number = None;
+  let mut tvdb_id = None;
+  let mut tmdb_id = None;
+  let mut imdb_id = None;
+  let mut order = None;
+  let mut language = None;
+  let mut year = None;
+  let mut episodes = vec![];
+  let mut extras = vec![];
+
+  for record in reader.records() {
+    let record = record.map_err(|e| e.to_string())?;
+    let (number, value) =
+      match (record.get(0), record.get(1)) {
+        (Some(number), Some(value)) => (number.to_owned(), value.to_owned()),
+        _ => return Err(format!("Expected number,name on line {}", record.position().map_or(0, |p| p.line())))
+      };
+
+    let air_date =
+      match record.get(2).filter(|air_date| !air_date.is_empty()) {
+        Some(air_date) => Some(air_date.parse::<AirDate>()?),
+        None => None
+      };
+
+    match number.as_str() {
+      SERIES_ROW => name = Some(value),
+      SEASON_ROW => season_number = Some(value.parse::<SeasonNumber>()?),
+      TVDB_ID_ROW => tvdb_id = Some(value),
+      TMDB_ID_ROW => tmdb_id = Some(value),
+      IMDB_ID_ROW => imdb_id = Some(value),
+      ORDER_ROW => order = Some(EpisodeOrder::from_str(&value, true)?),
+      LANGUAGE_ROW => language = Some(value),
+      YEAR_ROW => year = Some(value.parse::<u16>().map_err(|_| format!("Year: '{value}' is not a number"))?),
+      EXTRA_ROW => extras.push(ExtraDefinition { name: value, level: ExtrasLevel::Season, disc: None, title: None }),
+      _ => episodes.push(EpisodeDefinition { number: number.parse::<EpisodeNumber>()?, name: value, absolute_number: None, air_date, runtime: None, overview: None }),
+    }
+  }
+
+  let name = name.ok_or_else(|| format!("Expected a '{SERIES_ROW}' row with the name of the series"))?;
+  let season_number =
+    season_number
+      .or_else(|| episodes.first().map(|e| e.number.season()))
+      .ok_or_else(|| format!("Expected a '{SEASON_ROW}' row with the season number"))?;
+
+  Ok(
+    EpisodesDefinition {
+      metadata: SeriesMetaData { name, tvdb_id, tmdb_id, imdb_id, season_number, order, language, year },
+      episodes,
+      extras
+    }
+  )
+}
+
+fn write_csv(episodes_definition: &EpisodesDefinition) -> Result<String, String> {
+  if episodes_definition.extras.iter().any(|e| e.is_mapped() || e.level == ExtrasLevel::Series) {
+    return Err("CSV can only hold extras without a disc, title or series level".to_owned())
+  }
+
+  let metadata = &episodes_definition.metadata;
+  let mut writer = csv::Writer::from_writer(vec![]);
+
+  let metadata_rows =
+    [
+      Some((SERIES_ROW, metadata.name.clone())),
+      Some((SEASON_ROW, metadata.season_number.to_string())),
+      metadata.tvdb_id.clone().map(|id| (TVDB_ID_ROW, id)),
+      metadata.tmdb_id.clone().map(|id| (TMDB_ID_ROW, id)),
+      metadata.imdb_id.clone().map(|id| (IMDB_ID_ROW, id)),
+      metadata.order.map(|order| (ORDER_ROW, order.as_str().to_owned())),
+      metadata.language.clone().map(|language| (LANGUAGE_ROW, language)),
+      metadata.year.map(|year| (YEAR_ROW, year.to_string())),
+    ];
+
+  let episode_rows =
+    episodes_definition
+      .episodes
+      .iter()
+      .map(|e| (e.number.to_string(), e.name.clone(), e.air_date.map(|air_date| air_date.to_string())));
+
+  let extra_rows =
+    episodes_definition
+      .extras
+      .iter()
+      .map(|e| (EXTRA_ROW.to_owned(), e.name.clone(), None));
+
+  let rows =
+    metadata_rows
+      .into_iter()
+      .flatten()
+      .map(|(number, name)| (number.to_owned(), name, None))
+      .chain(episode_rows)
+      .chain(extra_rows);
+
+  // The air_date column is only written when there are air dates, so other files keep to number,name
+  let columns = if episodes_definition.episodes.iter().any(|e| e.air_date.is_some()) { 3 } else { 2 };
+
+  writer.write_record(&["number", "name", "air_date"][..columns]).map_err(|e| e.to_string())?;
+
+  for (number, name, air_date) in rows {
+    writer.write_record(&[number, name, air_date.unwrap_or_default()][..columns]).map_err(|e| e.to_string())?
+  }
+
+  writer
+    .into_inner()
+    .map_err(|e| e.to_string())
+    .and_then(|bytes| String::from_utf8(bytes).map_err(|e| e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{MovieMetadata, SeriesMetadataFile};
+    use pretty_assertions::assert_eq;
+
+    fn episodes_definition() -> EpisodesDefinition {
+      EpisodesDefinition {
+        metadata: SeriesMetaData {
+          name: "Thundercats".to_owned(),
+          tvdb_id: Some("70355".to_owned()),
+          tmdb_id: None,
+          imdb_id: None,
+          season_number: "1".parse().unwrap(),
+          order: Some(EpisodeOrder::Dvd),
+          language: Some("deu".to_owned()),
+          year: Some(1985)
+        },
+        episodes: vec![
+          EpisodeDefinition { number: "S01E01".parse().unwrap(), name: "Exodus".to_owned(), absolute_number: None, air_date: None, runtime: None, overview: None },
+          EpisodeDefinition { number: "S01E02".parse().unwrap(), name: "The Unholy Alliance, Part 1".to_owned(), absolute_number: None, air_date: Some("1985-01-30".parse().unwrap()), runtime: None, overview: None },
+        ],
+        extras: vec![
+          ExtraDefinition { name: "Behind the Scenes".to_owned(), level: ExtrasLevel::Season, disc: None, title: None }
+        ]
+      }
+    }
+
+    #[test]
+    fn test_for_path() {
+      assert_eq!(MetadataFormat::for_path(Path::new("series.YML"), None), MetadataFormat::Yaml);
+      assert_eq!(MetadataFormat::for_path(Path::new("series.csv"), None), MetadataFormat::Csv);
+      assert_eq!(MetadataFormat::for_path(Path::new("series.conf"), None), MetadataFormat::Json);
+      assert_eq!(MetadataFormat::for_path(Path::new("series.json"), Some(MetadataFormat::Toml)), MetadataFormat::Toml)
+    }
+
+    #[test]
+    fn test_round_trip() {
+      for format in [MetadataFormat::Json, MetadataFormat::Yaml, MetadataFormat::Toml, MetadataFormat::Csv] {
+        let encoded = format.encode(&episodes_definition()).unwrap();
+        let decoded: EpisodesDefinition = format.decode(&encoded).unwrap();
+
+        assert_eq!(decoded, episodes_definition(), "{}", format.as_str())
+      }
+    }
+
+    #[test]
+    fn test_decode_csv() {
+      let csv = "number,name\nseries,Thundercats\nS02E01, Mumm-Ra Lives!\nS02E02,\"Mumm-Ra Lives, Part 2\"\n";
+      let decoded = SeriesMetadataFile::decode(MetadataFormat::Csv, csv).unwrap();
+
+      let season =
+        match decoded {
+          SeriesMetadataFile::Season(season) => season,
+          SeriesMetadataFile::AllSeasons(_) => panic!("Expected a single season"),
+        };
+
+      assert_eq!(season.metadata.season_number.value(), 2);
+      assert_eq!(season.episodes[0].name, "Mumm-Ra Lives!");
+      assert_eq!(season.episodes[1].name, "Mumm-Ra Lives, Part 2")
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+      let json = r#"{ "metadata": { "name": "Thundercats", "season_number": "1", "network": "Syndicated" }, "episodes": [ { "number": "S01E01", "name": "Exodus", "rating": 7 } ] }"#;
+      let unknown_fields = MetadataFormat::Json.unknown_fields::<EpisodesDefinition>(json).unwrap();
+
+      assert_eq!(unknown_fields, vec!["metadata.network".to_owned(), "episodes.0.rating".to_owned()])
+    }
+
+    #[test]
+    fn test_decode_csv_without_series() {
+      let result: Result<EpisodesDefinition, _> = MetadataFormat::Csv.decode("number,name\nS01E01,Exodus\n");
+      assert_eq!(result, Err("Expected a 'series' row with the name of the series".to_owned()))
+    }
+
+    #[test]
+    fn test_decode_yaml_movie() {
+      let yaml = "name: The Big Lebowski\ntvdb_id: '659'\n";
+      let decoded = MovieMetadata::decode(MetadataFormat::Yaml, yaml).unwrap();
+
+      assert_eq!(decoded.movies()[0].name(), "The Big Lebowski")
+    }
+
+    #[test]
+    fn test_decode_box_set_error_position() {
+      let yaml = "movies:\n  - disc: disc1\n    name: Back to the Future\n    tvdb_id: [296]\n";
+      let error = MovieMetadata::decode(MetadataFormat::Yaml, yaml).unwrap_err();
+
+      // Box set movies are flattened, so the error is given for the whole movie
+      assert!(error.starts_with("movies[0]: invalid type: sequence, expected a string"), "{error}");
+      assert!(error.contains("line 2 column 5"), "{error}")
+    }
+
+    #[test]
+    fn test_has_field() {
+      assert!(MetadataFormat::Json.has_field(r#"{ "movies": [] }"#, "movies"));
+      assert!(MetadataFormat::Toml.has_field("[[movies]]\nname = 'Back to the Future'\n", "movies"));
+      assert!(!MetadataFormat::Yaml.has_field("name: The Big Lebowski\n", "movies"));
+      assert!(!MetadataFormat::Json.has_field("{ not json", "movies"))
+    }
+
+    #[test]
+    fn test_encode_csv_movie() {
+      let movie = MovieMetadata::Single(crate::models::MovieDefinition::new("The Big Lebowski".to_owned(), "659".to_owned()));
+      assert_eq!(MetadataFormat::Csv.encode(&movie), Err("CSV can only hold a single season of a series".to_owned()))
+    }
+}
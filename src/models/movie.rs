@@ -1,16 +1,70 @@
 use serde::{Deserialize, Serialize};
+use super::{id_tags, FileNameAndExt, MetadataFile, MetadataFormat};
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct MovieDefinition {
   name: String,
-  tvdb_id: String
+
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  tvdb_id: Option<String>,
+
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  tmdb_id: Option<String>,
+
+  /// The IMDb id of the movie, such as tt0133093. Only written to NFO files.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  imdb_id: Option<String>,
+
+  /// The language the name was retrieved in, when one was requested with --language
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  language: Option<String>,
+
+  /// The year the movie was released, when known. Tells remakes apart.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  year: Option<u16>,
 }
 
 impl MovieDefinition {
   pub fn new(name: String, tvdb_id: String) -> Self {
     Self {
       name,
-      tvdb_id
+      tvdb_id: Some(tvdb_id),
+      tmdb_id: None,
+      imdb_id: None,
+      language: None,
+      year: None
+    }
+  }
+
+  pub fn from_tmdb(name: String, tmdb_id: String) -> Self {
+    Self {
+      name,
+      tvdb_id: None,
+      tmdb_id: Some(tmdb_id),
+      imdb_id: None,
+      language: None,
+      year: None
+    }
+  }
+
+  pub fn with_language(self, language: Option<String>) -> Self {
+    Self {
+      language,
+      ..self
+    }
+  }
+
+  pub fn with_imdb_id(self, imdb_id: Option<String>) -> Self {
+    Self {
+      imdb_id,
+      ..self
+    }
+  }
+
+  pub fn with_year(self, year: Option<u16>) -> Self {
+    Self {
+      year,
+      ..self
     }
   }
 
@@ -18,8 +72,91 @@ impl MovieDefinition {
     &self.name
   }
 
-  pub fn tvdb_id(&self) -> &str {
-    &self.tvdb_id
+  pub fn year(&self) -> Option<u16> {
+    self.year
+  }
+
+  pub fn tvdb_id(&self) -> Option<&str> {
+    self.tvdb_id.as_deref()
+  }
+
+  pub fn tmdb_id(&self) -> Option<&str> {
+    self.tmdb_id.as_deref()
+  }
+
+  pub fn imdb_id(&self) -> Option<&str> {
+    self.imdb_id.as_deref()
+  }
+
+  pub fn id_tags(&self) -> Option<String> {
+    id_tags(self.tvdb_id.as_deref(), self.tmdb_id.as_deref())
+  }
+}
+
+/// A box set of movies, where each movie was ripped to its own disc directory
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct MovieBoxSetDefinition {
+  pub movies: Vec<BoxSetMovieDefinition>
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct BoxSetMovieDefinition {
+  /// The disc directory the movie was ripped to. Eg. disc1
+  pub disc: String,
+
+  /// The ripped file name within the disc directory, or the playlist or name MakeMKV listed for it. Only needed if the disc has more than one title.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub title: Option<String>,
+
+  #[serde(flatten)]
+  pub movie: MovieDefinition,
+}
+
+impl BoxSetMovieDefinition {
+  pub fn matches(&self, fne: &FileNameAndExt) -> bool {
+    let disc_matches =
+      fne
+        .path
+        .parent()
+        .and_then(|p| p.file_name())
+        .map(|d| d.to_string_lossy() == self.disc)
+        .unwrap_or(false);
+
+    let title_matches =
+      self
+        .title
+        .as_ref()
+        .map(|t| fne.is_title(t))
+        .unwrap_or(true);
+
+    disc_matches && title_matches
+  }
+}
+
+/// Movie metadata file content. Either a single movie or a box set of movies, which has a `movies` field.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum MovieMetadata {
+  BoxSet(MovieBoxSetDefinition),
+  Single(MovieDefinition),
+}
+
+impl MovieMetadata {
+  pub fn movies(&self) -> Vec<&MovieDefinition> {
+    match self {
+      MovieMetadata::BoxSet(box_set) => box_set.movies.iter().map(|m| &m.movie).collect(),
+      MovieMetadata::Single(movie) => vec![movie],
+    }
+  }
+}
+
+impl MetadataFile for MovieMetadata {
+  fn decode(format: MetadataFormat, content: &str) -> Result<Self, String> {
+    if format.has_field(content, "movies") {
+      format.decode(content).map(MovieMetadata::BoxSet)
+    } else {
+      format.decode(content).map(MovieMetadata::Single)
+    }
   }
 }
 
@@ -27,6 +164,7 @@ impl MovieDefinition {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::TitleInfo;
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -40,10 +178,96 @@ mod tests {
       let expected_movie_definition =
         MovieDefinition {
           name: "Star Wars: The Rise of Skywalker".to_owned(),
-          tvdb_id: "12879".to_owned()
+          tvdb_id: Some("12879".to_owned()),
+          tmdb_id: None,
+          imdb_id: None,
+          language: None,
+          year: None
         };
 
       let movie_definition: MovieDefinition = serde_json::from_str(conf).unwrap();
       assert_eq!(movie_definition, expected_movie_definition)
     }
+
+    #[test]
+    fn test_movie_metadata_deserialization() {
+      let single_conf = r#"
+        {
+          "name":"The Big Lebowski",
+          "tvdb_id":"659"
+        }"#;
+
+      let box_set_conf = r#"
+        {
+          "movies": [
+            { "disc":"disc1", "name":"Back to the Future", "tvdb_id":"296" },
+            { "disc":"disc2", "title":"title_t01.mkv", "name":"Back to the Future Part II", "tvdb_id":"297" }
+          ]
+        }"#;
+
+      let expected_single = MovieMetadata::Single(MovieDefinition::new("The Big Lebowski".to_owned(), "659".to_owned()));
+
+      let expected_box_set =
+        MovieMetadata::BoxSet(
+          MovieBoxSetDefinition {
+            movies: vec![
+              BoxSetMovieDefinition {
+                disc: "disc1".to_owned(),
+                title: None,
+                movie: MovieDefinition::new("Back to the Future".to_owned(), "296".to_owned())
+              },
+              BoxSetMovieDefinition {
+                disc: "disc2".to_owned(),
+                title: Some("title_t01.mkv".to_owned()),
+                movie: MovieDefinition::new("Back to the Future Part II".to_owned(), "297".to_owned())
+              },
+            ]
+          }
+        );
+
+      let single = MovieMetadata::decode(MetadataFormat::Json, single_conf).unwrap();
+      let box_set = MovieMetadata::decode(MetadataFormat::Json, box_set_conf).unwrap();
+
+      assert_eq!(single, expected_single);
+      assert_eq!(box_set, expected_box_set)
+    }
+
+    #[test]
+    fn test_tmdb_movie_deserialization() {
+      let conf = r#"
+        {
+          "name":"The Matrix",
+          "tmdb_id":"603"
+        }"#;
+
+      let movie_definition: MovieDefinition = serde_json::from_str(conf).unwrap();
+      assert_eq!(movie_definition, MovieDefinition::from_tmdb("The Matrix".to_owned(), "603".to_owned()));
+      assert_eq!(movie_definition.id_tags(), Some("{tmdb-603} [tmdbid-603]".to_owned()))
+    }
+
+    #[test]
+    fn test_box_set_movie_matches_disc_and_title() {
+      let movie = MovieDefinition::new("Back to the Future".to_owned(), "296".to_owned());
+      let by_disc = BoxSetMovieDefinition { disc: "disc1".to_owned(), title: None, movie: movie.clone() };
+      let by_title = BoxSetMovieDefinition { disc: "disc1".to_owned(), title: Some("title_t01.mkv".to_owned()), movie };
+
+      let path = std::path::Path::new("/rips/session1/disc1/title_t00.mkv");
+      let fne = FileNameAndExt::new(path, path.file_name().unwrap(), path.extension().unwrap());
+
+      assert!(by_disc.matches(&fne));
+      assert!(!by_title.matches(&fne))
+    }
+
+    #[test]
+    fn test_box_set_movie_matches_makemkv_playlist() {
+      let movie = MovieDefinition::new("Back to the Future Part II".to_owned(), "165".to_owned());
+      let by_playlist = BoxSetMovieDefinition { disc: "disc1".to_owned(), title: Some("00801.mpls".to_owned()), movie };
+
+      let path = std::path::Path::new("/rips/session1/disc1/title_t01.mkv");
+      let fne = FileNameAndExt::new(path, path.file_name().unwrap(), path.extension().unwrap());
+      let title_info = TitleInfo { index: 1, source_file_name: Some("00801.mpls".to_owned()), output_file_name: Some("title_t01.mkv".to_owned()), ..TitleInfo::default() };
+
+      assert!(!by_playlist.matches(&fne));
+      assert!(by_playlist.matches(&fne.with_title_info(Some(title_info))))
+    }
 }
@@ -0,0 +1,287 @@
+use std::fmt;
+use std::str::FromStr;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use super::EpisodeDefinition;
+
+const MAX_SEASON: u32 = 9999;
+const MAX_EPISODE: u32 = 9999;
+
+/// A season of a series. Season 0 holds the specials.
+///
+/// Written as a string, such as "1", so existing metadata files can still be read. Numbers are also accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(try_from = "StringOrNumber", into = "String")]
+pub struct SeasonNumber(u32);
+
+impl SeasonNumber {
+  pub fn new(season: u32) -> Result<Self, String> {
+    if season <= MAX_SEASON {
+      Ok(SeasonNumber(season))
+    } else {
+      Err(format!("Season: {season} is greater than the maximum of {MAX_SEASON}"))
+    }
+  }
+
+  pub fn value(&self) -> u32 {
+    self.0
+  }
+}
+
+impl fmt::Display for SeasonNumber {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.0)
+  }
+}
+
+impl FromStr for SeasonNumber {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    value
+      .trim()
+      .parse::<u32>()
+      .map_err(|_| format!("Season: '{value}' is not a number"))
+      .and_then(SeasonNumber::new)
+  }
+}
+
+impl From<SeasonNumber> for String {
+  fn from(season: SeasonNumber) -> Self {
+    season.to_string()
+  }
+}
+
+impl TryFrom<StringOrNumber> for SeasonNumber {
+  type Error = String;
+
+  fn try_from(value: StringOrNumber) -> Result<Self, Self::Error> {
+    match value {
+      StringOrNumber::String(value) => value.parse(),
+      StringOrNumber::Number(value) => SeasonNumber::new(value),
+    }
+  }
+}
+
+/// The number of an episode within a season.
+///
+/// Parsed from S01E02, 1x02 or 102, and always written as S01E02.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(try_from = "StringOrNumber", into = "String")]
+pub struct EpisodeNumber {
+  season: SeasonNumber,
+  episode: u32,
+}
+
+impl EpisodeNumber {
+  pub fn new(season: SeasonNumber, episode: u32) -> Result<Self, String> {
+    if episode <= MAX_EPISODE {
+      Ok(EpisodeNumber { season, episode })
+    } else {
+      Err(format!("Episode: {episode} is greater than the maximum of {MAX_EPISODE}"))
+    }
+  }
+
+  /// Creates an episode number from numbers supplied by a provider
+  pub fn from_numbers(season: u32, episode: u32) -> Result<Self, String> {
+    SeasonNumber::new(season).and_then(|season| EpisodeNumber::new(season, episode))
+  }
+
+  pub fn season(&self) -> SeasonNumber {
+    self.season
+  }
+
+  pub fn episode(&self) -> u32 {
+    self.episode
+  }
+}
+
+impl fmt::Display for EpisodeNumber {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "S{:02}E{:02}", self.season.value(), self.episode)
+  }
+}
+
+impl FromStr for EpisodeNumber {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    let invalid_number = || format!("Episode number: '{value}' is not in S01E02, 1x02 or 102 format");
+    let number = value.trim().to_uppercase();
+
+    let (season, episode) =
+      if let Some(season_and_episode) = number.strip_prefix('S') {
+        season_and_episode.split_once('E').ok_or_else(invalid_number)?
+      } else if let Some(season_and_episode) = number.split_once('X') {
+        season_and_episode
+      } else if (3..=4).contains(&number.len()) && number.chars().all(|c| c.is_ascii_digit()) {
+        // The last two digits are the episode. Given: 102, returns: (1, 02)
+        number.split_at(number.len() - 2)
+      } else {
+        return Err(invalid_number())
+      };
+
+    let season = season.parse::<u32>().map_err(|_| invalid_number())?;
+    let episode = episode.parse::<u32>().map_err(|_| invalid_number())?;
+
+    EpisodeNumber::from_numbers(season, episode)
+  }
+}
+
+impl From<EpisodeNumber> for String {
+  fn from(number: EpisodeNumber) -> Self {
+    number.to_string()
+  }
+}
+
+impl TryFrom<StringOrNumber> for EpisodeNumber {
+  type Error = String;
+
+  fn try_from(value: StringOrNumber) -> Result<Self, Self::Error> {
+    match value {
+      StringOrNumber::String(value) => value.parse(),
+      StringOrNumber::Number(value) => value.to_string().parse(),
+    }
+  }
+}
+
+/// How episode numbers are written in the names of renamed files
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum NumberingStyle {
+  #[default]
+  #[value(name = "S01E01")]
+  Upper,
+
+  #[value(name = "s01e01")]
+  Lower,
+
+  #[value(name = "1x01")]
+  Cross,
+
+  /// The episode number across all seasons, such as 001. Requires an absolute_number for each episode.
+  Absolute,
+
+  /// Both the season and absolute numbers, such as S01E01 - 001. Requires an absolute_number for each episode.
+  #[value(name = "S01E01-absolute")]
+  UpperAbsolute,
+
+  /// The date the episode first aired, such as 2003-04-12, for daily and talk shows. Requires an air_date for each episode.
+  /// File names always start with the name of the series, as media servers need it to match episodes by date.
+  Date,
+}
+
+/// A numbering style along with the number of digits to pad episode numbers to
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Numbering {
+  pub style: NumberingStyle,
+
+  /// Defaults to 2 digits, or 3 for absolute numbers. Numbers with more digits are not shortened.
+  /// When set, a number with more digits is an error, as it would not sort with the other files.
+  /// When both numbers are written, only the absolute number is padded to this width.
+  pub width: Option<usize>,
+}
+
+impl Numbering {
+  pub fn format(&self, episode: &EpisodeDefinition) -> Result<String, String> {
+    let number = episode.number;
+
+    if self.style == NumberingStyle::Date {
+      return episode.air_date.map(|air_date| air_date.to_string()).ok_or_else(|| format!("{number} has no air_date"))
+    }
+
+    let (value, default_width) =
+      match self.style {
+        NumberingStyle::Absolute | NumberingStyle::UpperAbsolute => {
+          let absolute_number = episode.absolute_number.ok_or_else(|| format!("{number} has no absolute_number"))?;
+          (absolute_number, 3)
+        },
+        _ => (number.episode(), 2)
+      };
+
+    let width = self.width.unwrap_or(default_width);
+
+    if self.width.is_some() && value.to_string().len() > width {
+      return Err(format!("{value} has more than {width} digits"))
+    }
+
+    let season = number.season().value();
+    let episode = format!("{value:0width$}");
+
+    let formatted =
+      match self.style {
+        NumberingStyle::Upper => format!("S{season:02}E{episode}"),
+        NumberingStyle::Lower => format!("s{season:02}e{episode}"),
+        NumberingStyle::Cross => format!("{season}x{episode}"),
+        NumberingStyle::Absolute => episode,
+        NumberingStyle::UpperAbsolute => format!("{number} - {episode}"),
+        NumberingStyle::Date => unreachable!("air dates are not padded"),
+      };
+
+    Ok(formatted)
+  }
+}
+
+// YAML and TOML files can hold numbers unquoted
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrNumber {
+  String(String),
+  Number(u32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_episode_number() {
+      let expected = EpisodeNumber::from_numbers(1, 2).unwrap();
+
+      assert_eq!("S01E02".parse::<EpisodeNumber>(), Ok(expected));
+      assert_eq!("s1e2".parse::<EpisodeNumber>(), Ok(expected));
+      assert_eq!("1x02".parse::<EpisodeNumber>(), Ok(expected));
+      assert_eq!("102".parse::<EpisodeNumber>(), Ok(expected));
+      assert_eq!("1012".parse::<EpisodeNumber>(), Ok(EpisodeNumber::from_numbers(10, 12).unwrap()));
+      assert_eq!("Episode 5".parse::<EpisodeNumber>(), Err("Episode number: 'Episode 5' is not in S01E02, 1x02 or 102 format".to_owned()));
+      assert_eq!("S01E10000".parse::<EpisodeNumber>(), Err("Episode: 10000 is greater than the maximum of 9999".to_owned()))
+    }
+
+    #[test]
+    fn test_format_episode_number() {
+      assert_eq!(EpisodeNumber::from_numbers(1, 2).unwrap().to_string(), "S01E02");
+      assert_eq!(EpisodeNumber::from_numbers(12, 123).unwrap().to_string(), "S12E123")
+    }
+
+    #[test]
+    fn test_numbering() {
+      let episode = EpisodeDefinition { number: "S02E05".parse().unwrap(), name: "Mumm-Ra Lives!".to_owned(), absolute_number: Some(70), air_date: None, runtime: None, overview: None };
+      let numbering = |style, width| Numbering { style, width }.format(&episode);
+
+      assert_eq!(numbering(NumberingStyle::Upper, None), Ok("S02E05".to_owned()));
+      assert_eq!(numbering(NumberingStyle::Lower, Some(3)), Ok("s02e005".to_owned()));
+      assert_eq!(numbering(NumberingStyle::Cross, None), Ok("2x05".to_owned()));
+      assert_eq!(numbering(NumberingStyle::Absolute, None), Ok("070".to_owned()));
+      assert_eq!(numbering(NumberingStyle::Absolute, Some(1)), Err("70 has more than 1 digits".to_owned()));
+      assert_eq!(numbering(NumberingStyle::UpperAbsolute, Some(4)), Ok("S02E05 - 0070".to_owned()));
+
+      let episode = EpisodeDefinition { absolute_number: None, ..episode.clone() };
+      assert_eq!(Numbering { style: NumberingStyle::Absolute, width: None }.format(&episode), Err("S02E05 has no absolute_number".to_owned()));
+      assert_eq!(Numbering { style: NumberingStyle::Date, width: None }.format(&episode), Err("S02E05 has no air_date".to_owned()));
+
+      let episode = EpisodeDefinition { air_date: Some("1986-09-08".parse().unwrap()), ..episode };
+      assert_eq!(Numbering { style: NumberingStyle::Date, width: Some(2) }.format(&episode), Ok("1986-09-08".to_owned()))
+    }
+
+    #[test]
+    fn test_serde() {
+      let season: SeasonNumber = serde_json::from_str(r#""01""#).unwrap();
+      assert_eq!(season, SeasonNumber::new(1).unwrap());
+      assert_eq!(serde_json::to_string(&season).unwrap(), r#""1""#);
+
+      let number: EpisodeNumber = serde_yaml::from_str("102").unwrap();
+      assert_eq!(serde_json::to_string(&number).unwrap(), r#""S01E02""#);
+
+      assert!(serde_json::from_str::<SeasonNumber>(r#""one""#).is_err())
+    }
+}
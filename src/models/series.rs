@@ -1,23 +1,195 @@
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
+use super::{id_tags, AirDate, EpisodeNumber, FileNameAndExt, MetadataFile, MetadataFormat, SeasonNumber};
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct SeriesMetaData {
   pub name: String,
-  pub tvdb_id: String,
-  pub season_number: String,
+
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub tvdb_id: Option<String>,
+
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub tmdb_id: Option<String>,
+
+  /// The IMDb id of the series, such as tt0088631. Only written to NFO files, as media servers don't read it from names.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub imdb_id: Option<String>,
+
+  pub season_number: SeasonNumber,
+
+  /// The episode order the season was retrieved in, when known
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub order: Option<EpisodeOrder>,
+
+  /// The language the names were retrieved in, when one was requested with --language
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub language: Option<String>,
+
+  /// The year the series first aired, when known. Tells remakes and reboots apart.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub year: Option<u16>,
+}
+
+impl SeriesMetaData {
+  pub fn id_tags(&self) -> Option<String> {
+    id_tags(self.tvdb_id.as_deref(), self.tmdb_id.as_deref())
+  }
 }
 
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct EpisodesDefinition {
   pub metadata: SeriesMetaData,
-  pub episodes: Vec<EpisodeDefinition>
+  pub episodes: Vec<EpisodeDefinition>,
+
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub extras: Vec<ExtraDefinition>,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+/// Every season of a series, as exported with `--all-seasons`
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct SeasonsDefinition {
+  pub seasons: Vec<EpisodesDefinition>,
+}
+
+impl SeasonsDefinition {
+  pub fn season(&self, season_number: u32) -> Option<&EpisodesDefinition> {
+    self
+      .seasons
+      .iter()
+      .find(|s| s.metadata.season_number.value() == season_number)
+  }
+
+  pub fn season_numbers(&self) -> Vec<SeasonNumber> {
+    self
+      .seasons
+      .iter()
+      .map(|s| s.metadata.season_number)
+      .collect()
+  }
+}
+
+/// A series metadata file can contain a single season or every season of a series, which has a `seasons` field
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum SeriesMetadataFile {
+  Season(EpisodesDefinition),
+  AllSeasons(SeasonsDefinition),
+}
+
+impl MetadataFile for SeriesMetadataFile {
+  fn decode(format: MetadataFormat, content: &str) -> Result<Self, String> {
+    if format.has_field(content, "seasons") {
+      format.decode(content).map(SeriesMetadataFile::AllSeasons)
+    } else {
+      format.decode(content).map(SeriesMetadataFile::Season)
+    }
+  }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct EpisodeDefinition {
-  pub number: String,
+  pub number: EpisodeNumber,
   pub name: String,
+
+  /// The number of the episode counted across all seasons, when known
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub absolute_number: Option<u32>,
+
+  /// The date the episode first aired, when known
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub air_date: Option<AirDate>,
+
+  /// The length of the episode in minutes, when known. Used to check the durations of ripped files.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub runtime: Option<u32>,
+
+  /// A summary of the episode, when known
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub overview: Option<String>,
+}
+
+/// Bonus material such as featurettes. These are kept out of episode matching.
+///
+/// Extras are matched to ripped files either explicitly through `disc` and `title`, or positionally
+/// to files in `discN-extras` directories.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ExtraDefinition {
+  pub name: String,
+
+  #[serde(default)]
+  pub level: ExtrasLevel,
+
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub disc: Option<String>,
+
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub title: Option<String>,
+}
+
+impl ExtraDefinition {
+  pub fn is_mapped(&self) -> bool {
+    self.disc.is_some() || self.title.is_some()
+  }
+
+  pub fn matches(&self, fne: &FileNameAndExt) -> bool {
+    let disc_matches =
+      self
+        .disc
+        .as_ref()
+        .map(|disc| {
+          fne
+            .path
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|d| d.to_string_lossy() == disc.as_str())
+            .unwrap_or(false)
+        })
+        .unwrap_or(true);
+
+    let title_matches =
+      self
+        .title
+        .as_ref()
+        .map(|t| fne.is_title(t))
+        .unwrap_or(true);
+
+    self.is_mapped() && disc_matches && title_matches
+  }
+}
+
+/// Whether an extra belongs to the season being renamed or to the series as a whole
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExtrasLevel {
+  #[default]
+  Season,
+  Series,
+}
+
+/// The order episodes are numbered in. Discs usually follow the DVD order.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum EpisodeOrder {
+  /// The order episodes were broadcast in
+  Aired,
+
+  /// The order episodes appear on the DVD release
+  Dvd,
+
+  /// A single episode count across all seasons
+  Absolute,
+}
+
+impl EpisodeOrder {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      EpisodeOrder::Aired => "aired",
+      EpisodeOrder::Dvd => "dvd",
+      EpisodeOrder::Absolute => "absolute",
+    }
+  }
 }
 
 #[cfg(test)]
@@ -47,28 +219,52 @@ mod tests {
       let expected_episodes =
         vec![
           EpisodeDefinition {
-            number:"S01E01".to_string(),
-            name:"Exodus".to_string()
+            number: "S01E01".parse().unwrap(),
+            name:"Exodus".to_string(),
+            absolute_number: None,
+            air_date: None,
+            runtime: None,
+            overview: None
           },
           EpisodeDefinition {
-            number:"S01E02".to_string(),
-            name:"The Unholy Alliance".to_string()
+            number: "S01E02".parse().unwrap(),
+            name:"The Unholy Alliance".to_string(),
+            absolute_number: None,
+            air_date: None,
+            runtime: None,
+            overview: None
           },
           EpisodeDefinition {
-            number:"S01E03".to_string(),
-            name:"Berbils".to_string()
+            number: "S01E03".parse().unwrap(),
+            name:"Berbils".to_string(),
+            absolute_number: None,
+            air_date: None,
+            runtime: None,
+            overview: None
           },
           EpisodeDefinition {
-            number:"S01E04".to_string(),
-            name:"The Slaves of Castle Plun-Darr".to_string()
+            number: "S01E04".parse().unwrap(),
+            name:"The Slaves of Castle Plun-Darr".to_string(),
+            absolute_number: None,
+            air_date: None,
+            runtime: None,
+            overview: None
           },
           EpisodeDefinition {
-            number:"S01E05".to_string(),
-            name:"Pumm-Ra".to_string()
+            number: "S01E05".parse().unwrap(),
+            name:"Pumm-Ra".to_string(),
+            absolute_number: None,
+            air_date: None,
+            runtime: None,
+            overview: None
           },
           EpisodeDefinition {
-            number:"S01E06".to_string(),
-            name:"The Terror of Hammerhand".to_string()
+            number: "S01E06".parse().unwrap(),
+            name:"The Terror of Hammerhand".to_string(),
+            absolute_number: None,
+            air_date: None,
+            runtime: None,
+            overview: None
           }
         ];
 
@@ -76,13 +272,101 @@ mod tests {
         EpisodesDefinition {
           metadata: SeriesMetaData {
             name: "Thundercats".to_string(),
-            tvdb_id: "70355".to_string(),
-            season_number: "1".to_string()
+            tvdb_id: Some("70355".to_string()),
+            tmdb_id: None,
+            imdb_id: None,
+            season_number: "1".parse().unwrap(),
+            order: None,
+            language: None,
+            year: None
           },
-          episodes: expected_episodes
+          episodes: expected_episodes,
+          extras: vec![]
         };
 
       let episodes_definition: EpisodesDefinition = serde_json::from_str(conf).unwrap();
       assert_eq!(episodes_definition, expected_episodes_definition)
     }
+
+    #[test]
+    fn test_extras_deserialization() {
+      let conf = r#"{
+        "metadata": {
+          "name":"Thundercats",
+          "tvdb_id":"70355",
+          "season_number":"1"
+        },
+        "episodes": [
+          { "number":"S01E01", "name":"Exodus"}
+        ],
+        "extras": [
+          { "name":"Behind the Scenes"},
+          { "name":"Creating the Cats", "level":"series", "disc":"disc4", "title":"title_t03.mkv"}
+        ]
+      }"#;
+
+      let expected_extras =
+        vec![
+          ExtraDefinition {
+            name: "Behind the Scenes".to_string(),
+            level: ExtrasLevel::Season,
+            disc: None,
+            title: None
+          },
+          ExtraDefinition {
+            name: "Creating the Cats".to_string(),
+            level: ExtrasLevel::Series,
+            disc: Some("disc4".to_string()),
+            title: Some("title_t03.mkv".to_string())
+          }
+        ];
+
+      let episodes_definition: EpisodesDefinition = serde_json::from_str(conf).unwrap();
+      assert_eq!(episodes_definition.extras, expected_extras)
+    }
+
+    #[test]
+    fn test_series_id_tags() {
+      let tvdb_series = SeriesMetaData { name: "Thundercats".to_string(), tvdb_id: Some("70355".to_string()), tmdb_id: Some("1067".to_string()), imdb_id: None, season_number: "1".parse().unwrap(), order: None, language: None, year: None };
+      let tmdb_series = SeriesMetaData { tvdb_id: None, ..tvdb_series.clone() };
+
+      assert_eq!(tvdb_series.id_tags(), Some("{tvdb-70355} [tvdbid-70355]".to_string()));
+      assert_eq!(tmdb_series.id_tags(), Some("{tmdb-1067} [tmdbid-1067]".to_string()))
+    }
+
+    #[test]
+    fn test_order_serialization() {
+      let metadata = SeriesMetaData { name: "Firefly".to_string(), tvdb_id: Some("78874".to_string()), tmdb_id: None, imdb_id: None, season_number: "1".parse().unwrap(), order: Some(EpisodeOrder::Dvd), language: None, year: None };
+      let json = serde_json::to_string(&metadata).unwrap();
+
+      assert_eq!(json, r#"{"name":"Firefly","tvdb_id":"78874","season_number":"1","order":"dvd"}"#);
+      assert_eq!(serde_json::from_str::<SeriesMetaData>(&json).unwrap(), metadata)
+    }
+
+    #[test]
+    fn test_all_seasons_deserialization() {
+      let conf = r#"{
+        "seasons": [
+          {
+            "metadata": { "name":"Thundercats", "tvdb_id":"70355", "season_number":"1" },
+            "episodes": [ { "number":"S01E01", "name":"Exodus"} ]
+          },
+          {
+            "metadata": { "name":"Thundercats", "tvdb_id":"70355", "season_number":"2" },
+            "episodes": [ { "number":"S02E01", "name":"Mumm-Ra Lives!"} ]
+          }
+        ]
+      }"#;
+
+      let metadata_file = SeriesMetadataFile::decode(MetadataFormat::Json, conf).unwrap();
+
+      match metadata_file {
+        SeriesMetadataFile::AllSeasons(seasons_definition) => {
+          assert_eq!(seasons_definition.season_numbers(), vec![SeasonNumber::new(1).unwrap(), SeasonNumber::new(2).unwrap()]);
+          assert_eq!(seasons_definition.season(2).map(|s| s.episodes[0].name.as_str()), Some("Mumm-Ra Lives!"));
+          assert_eq!(seasons_definition.season(3), None)
+        },
+        SeriesMetadataFile::Season(_) => panic!("Expected all seasons")
+      }
+    }
 }
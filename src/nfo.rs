@@ -0,0 +1,185 @@
+use std::fs;
+use std::path::PathBuf;
+use crate::models::*;
+
+// Kodi and Jellyfin read these from the series and movie directories
+pub const TVSHOW_NFO: &str = "tvshow.nfo";
+pub const MOVIE_NFO: &str = "movie.nfo";
+pub const NFO_EXT: &str = "nfo";
+
+const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+
+/// An NFO file to write once the encode directories have been created
+#[derive(Debug, Clone, PartialEq)]
+pub struct NfoFile {
+  pub path: PathBuf,
+  pub content: String,
+}
+
+impl NfoFile {
+  pub fn new(path: PathBuf, content: String) -> Self {
+    Self {
+      path,
+      content
+    }
+  }
+}
+
+pub fn tvshow_nfo(metadata: &SeriesMetaData) -> String {
+  let elements =
+    [
+      Some(element("title", &metadata.name)),
+      metadata.year.map(|year| element("year", &year.to_string())),
+    ]
+    .into_iter()
+    .flatten()
+    .chain(unique_ids(metadata.tvdb_id.as_deref(), metadata.tmdb_id.as_deref(), metadata.imdb_id.as_deref()));
+
+  document("tvshow", elements)
+}
+
+pub fn episode_nfo(metadata: &SeriesMetaData, episode: &EpisodeDefinition) -> String {
+  let elements =
+    [
+      Some(element("title", &episode.name)),
+      Some(element("showtitle", &metadata.name)),
+      Some(element("season", &episode.number.season().to_string())),
+      Some(element("episode", &episode.number.episode().to_string())),
+      episode.air_date.map(|air_date| element("aired", &air_date.to_string())),
+      episode.overview.as_ref().map(|overview| element("plot", overview)),
+      episode.runtime.map(|runtime| element("runtime", &runtime.to_string())),
+    ]
+    .into_iter()
+    .flatten();
+
+  document("episodedetails", elements)
+}
+
+pub fn movie_nfo(movie: &MovieDefinition) -> String {
+  let elements =
+    [
+      Some(element("title", movie.name())),
+      movie.year().map(|year| element("year", &year.to_string())),
+    ]
+    .into_iter()
+    .flatten()
+    .chain(unique_ids(movie.tvdb_id(), movie.tmdb_id(), movie.imdb_id()));
+
+  document("movie", elements)
+}
+
+/// Writes each NFO file, replacing any written for an earlier season
+pub fn write_nfo_files(nfo_files: &[NfoFile]) -> R {
+  nfo_files
+    .iter()
+    .try_for_each(|nfo_file| {
+      fs::write(&nfo_file.path, &nfo_file.content)
+        .map_err(|e| RenamerError::CouldNotWriteNfoFile(nfo_file.path.clone(), e.to_string()))
+    })
+}
+
+// The first known id is the default, preferring TVDB as names do
+fn unique_ids(tvdb_id: Option<&str>, tmdb_id: Option<&str>, imdb_id: Option<&str>) -> Vec<String> {
+  [("tvdb", tvdb_id), ("tmdb", tmdb_id), ("imdb", imdb_id)]
+    .into_iter()
+    .filter_map(|(provider, id)| id.map(|id| (provider, id)))
+    .enumerate()
+    .map(|(index, (provider, id))| {
+      let default = if index == 0 { r#" default="true""# } else { "" };
+      format!(r#"<uniqueid type="{provider}"{default}>{}</uniqueid>"#, escape(id))
+    })
+    .collect()
+}
+
+fn element(name: &str, value: &str) -> String {
+  format!("<{name}>{}</{name}>", escape(value))
+}
+
+fn document<I: Iterator<Item = String>>(root: &str, elements: I) -> String {
+  let body: String = elements.map(|element| format!("  {element}\n")).collect();
+  format!("{XML_DECLARATION}\n<{root}>\n{body}</{root}>\n")
+}
+
+fn escape(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn metadata() -> SeriesMetaData {
+      SeriesMetaData {
+        name: "Thundercats".to_owned(),
+        tvdb_id: Some("70355".to_owned()),
+        tmdb_id: Some("1067".to_owned()),
+        imdb_id: Some("tt0088631".to_owned()),
+        season_number: "1".parse().unwrap(),
+        order: None,
+        language: None,
+        year: Some(1985)
+      }
+    }
+
+    #[test]
+    fn test_tvshow_nfo() {
+      let expected = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<tvshow>
+  <title>Thundercats</title>
+  <year>1985</year>
+  <uniqueid type="tvdb" default="true">70355</uniqueid>
+  <uniqueid type="tmdb">1067</uniqueid>
+  <uniqueid type="imdb">tt0088631</uniqueid>
+</tvshow>
+"#;
+
+      assert_eq!(tvshow_nfo(&metadata()), expected)
+    }
+
+    #[test]
+    fn test_episode_nfo() {
+      let episode =
+        EpisodeDefinition {
+          number: "S01E02".parse().unwrap(),
+          name: "The Unholy Alliance".to_owned(),
+          absolute_number: None,
+          air_date: Some("1985-01-30".parse().unwrap()),
+          runtime: Some(22),
+          overview: Some("Mumm-Ra joins forces with the Mutants & <Slithe>.".to_owned())
+        };
+
+      let expected = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<episodedetails>
+  <title>The Unholy Alliance</title>
+  <showtitle>Thundercats</showtitle>
+  <season>1</season>
+  <episode>2</episode>
+  <aired>1985-01-30</aired>
+  <plot>Mumm-Ra joins forces with the Mutants &amp; &lt;Slithe&gt;.</plot>
+  <runtime>22</runtime>
+</episodedetails>
+"#;
+
+      assert_eq!(episode_nfo(&metadata(), &episode), expected)
+    }
+
+    #[test]
+    fn test_movie_nfo() {
+      let movie = MovieDefinition::from_tmdb("The Matrix".to_owned(), "603".to_owned()).with_imdb_id(Some("tt0133093".to_owned()));
+
+      let expected = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<movie>
+  <title>The Matrix</title>
+  <uniqueid type="tmdb" default="true">603</uniqueid>
+  <uniqueid type="imdb">tt0133093</uniqueid>
+</movie>
+"#;
+
+      assert_eq!(movie_nfo(&movie), expected)
+    }
+}
@@ -0,0 +1,634 @@
+use reqwest::RequestBuilder;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use crate::cli::ProviderArgs;
+use crate::http_client::HttpClient;
+use crate::metadata_downloader::{decode_json_response, url_path_segments};
+use crate::metadata_provider::{translated, MediaKind, MetadataProvider, SearchQuery, SearchResult};
+use crate::models::{AirDate, Artwork, EpisodeDefinition, EpisodeNumber, EpisodeOrder, EpisodesDefinition, MovieDefinition, MovieMetadata, RenamerError, SeasonNumber, SeriesMetaData};
+
+pub const TMDB_HOST: &str = "themoviedb.org";
+const TMDB_WEBSITE: &str = "https://www.themoviedb.org";
+const TMDB_IMAGE_URL: &str = "https://image.tmdb.org/t/p/original";
+
+/// Client for the TMDB v3 API: https://developer.themoviedb.org/reference/intro/getting-started
+///
+/// Both v3 API keys and v4 read access tokens are supported. Read access tokens are sent as a bearer token,
+/// while API keys are sent as the `api_key` query parameter.
+pub struct TmdbApiClient {
+  http: HttpClient,
+  base_url: String,
+  api_key: String,
+  language: Option<String>,
+  image_base_url: String,
+}
+
+/// A TMDB season URL. Example: https://www.themoviedb.org/tv/1399-game-of-thrones/season/1
+#[derive(Debug, PartialEq)]
+pub struct TmdbSeasonUrl {
+  pub id: u64,
+  pub season: u32,
+}
+
+impl TmdbSeasonUrl {
+  pub fn parse(url: &str) -> Result<Self, RenamerError> {
+    let invalid_url = || RenamerError::InvalidTmdbUrl(url.to_owned());
+
+    match url_path_segments(url).as_slice() {
+      ["tv", id, "season", season] =>
+        Ok(
+          TmdbSeasonUrl {
+            id: parse_id(id).ok_or_else(invalid_url)?,
+            season: season.parse().map_err(|_| invalid_url())?,
+          }
+        ),
+      _ => Err(invalid_url())
+    }
+  }
+}
+
+/// A TMDB series URL, or any of its season URLs. Example: https://www.themoviedb.org/tv/1399-game-of-thrones
+/// A series id can also be used.
+#[derive(Debug, PartialEq)]
+pub struct TmdbSeriesUrl {
+  pub id: u64,
+}
+
+impl TmdbSeriesUrl {
+  pub fn parse(url: &str) -> Result<Self, RenamerError> {
+    let invalid_url = || RenamerError::InvalidTmdbUrl(url.to_owned());
+
+    if let Ok(id) = url.trim().parse() {
+      return Ok(TmdbSeriesUrl { id })
+    }
+
+    match url_path_segments(url).as_slice() {
+      ["tv", id, ..] => Ok(TmdbSeriesUrl { id: parse_id(id).ok_or_else(invalid_url)? }),
+      _ => Err(invalid_url())
+    }
+  }
+}
+
+/// A TMDB movie URL. Example: https://www.themoviedb.org/movie/603-the-matrix
+#[derive(Debug, PartialEq)]
+pub struct TmdbMovieUrl {
+  pub id: u64,
+}
+
+impl TmdbMovieUrl {
+  pub fn parse(url: &str) -> Result<Self, RenamerError> {
+    let invalid_url = || RenamerError::InvalidTmdbUrl(url.to_owned());
+
+    match url_path_segments(url).as_slice() {
+      ["movie", id] => Ok(TmdbMovieUrl { id: parse_id(id).ok_or_else(invalid_url)? }),
+      _ => Err(invalid_url())
+    }
+  }
+}
+
+/// A candidate returned from a TMDB search
+#[derive(Debug, Clone, PartialEq)]
+struct TmdbSearchResult {
+  id: u64,
+  name: String,
+  year: Option<String>,
+}
+
+// TMDB ids are followed by an optional slug. Eg. 603-the-matrix
+fn parse_id(id_and_slug: &str) -> Option<u64> {
+  id_and_slug
+    .split('-')
+    .next()
+    .and_then(|id| id.parse().ok())
+}
+
+#[derive(Deserialize)]
+struct ApiSeries {
+  id: u64,
+  name: String,
+  first_air_date: Option<String>,
+  poster_path: Option<String>,
+  backdrop_path: Option<String>,
+  #[serde(default)]
+  seasons: Vec<ApiSeasonSummary>,
+}
+
+#[derive(Deserialize)]
+struct ApiSeasonSummary {
+  season_number: u32,
+}
+
+#[derive(Deserialize)]
+struct ApiSeason {
+  episodes: Vec<ApiEpisode>,
+  poster_path: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiEpisode {
+  season_number: u32,
+  episode_number: u32,
+  name: String,
+  air_date: Option<String>,
+  runtime: Option<u32>,
+  overview: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiMovie {
+  id: u64,
+  title: String,
+  release_date: Option<String>,
+  imdb_id: Option<String>,
+  poster_path: Option<String>,
+  backdrop_path: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiSearchResults<T> {
+  results: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct ApiSeriesSearchResult {
+  id: u64,
+  name: String,
+  first_air_date: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiMovieSearchResult {
+  id: u64,
+  title: String,
+  release_date: Option<String>,
+}
+
+impl TmdbApiClient {
+  pub fn new(http: HttpClient, base_url: &str, api_key: &str) -> Self {
+    Self {
+      http,
+      base_url: base_url.trim_end_matches('/').to_owned(),
+      api_key: api_key.to_owned(),
+      language: None,
+      image_base_url: TMDB_IMAGE_URL.to_owned(),
+    }
+  }
+
+  /// Where images are downloaded from, as the API only returns their paths
+  pub fn with_image_base_url(self, image_base_url: &str) -> Self {
+    Self {
+      image_base_url: image_base_url.trim_end_matches('/').to_owned(),
+      ..self
+    }
+  }
+
+  /// Retrieves names in the language, such as de or de-DE, falling back to English for any that are not translated
+  pub fn with_language(self, language: Option<String>) -> Self {
+    Self {
+      language,
+      ..self
+    }
+  }
+
+  pub fn from_args(provider_args: &ProviderArgs) -> Result<Self, RenamerError> {
+    let api_key =
+      provider_args
+        .tmdb_api_key
+        .as_ref()
+        .ok_or(RenamerError::MissingTmdbApiKey)?;
+
+    let http = HttpClient::from_args(&provider_args.http)?;
+    Ok(
+      Self::new(http, &provider_args.tmdb_api_url, api_key)
+        .with_language(provider_args.language.clone())
+        .with_image_base_url(&provider_args.tmdb_image_url)
+    )
+  }
+
+  async fn search_series(&self, name: &str) -> Result<Vec<TmdbSearchResult>, RenamerError> {
+    let results: ApiSearchResults<ApiSeriesSearchResult> = self.get("/search/tv", &[("query", name)]).await?;
+
+    Ok(
+      results
+        .results
+        .into_iter()
+        .map(|r| TmdbSearchResult { id: r.id, name: r.name, year: year_of(r.first_air_date) })
+        .collect()
+    )
+  }
+
+  async fn search_movie(&self, name: &str, year: Option<u16>) -> Result<Vec<TmdbSearchResult>, RenamerError> {
+    let year = year.map(|y| y.to_string());
+    let mut query = vec![("query", name)];
+    if let Some(year) = year.as_deref() {
+      query.push(("year", year))
+    }
+
+    let results: ApiSearchResults<ApiMovieSearchResult> = self.get("/search/movie", &query).await?;
+
+    Ok(
+      results
+        .results
+        .into_iter()
+        .map(|r| TmdbSearchResult { id: r.id, name: r.title, year: year_of(r.release_date) })
+        .collect()
+    )
+  }
+
+  // TMDB fills in missing translations inconsistently, so the default language is retrieved as well to fall back to
+  async fn get_translated<T: DeserializeOwned>(&self, path: &str) -> Result<(T, Option<T>), RenamerError> {
+    let default = self.get(path, &[]).await?;
+
+    match &self.language {
+      Some(language) => Ok((default, Some(self.get(path, &[("language", language)]).await?))),
+      None => Ok((default, None))
+    }
+  }
+
+  // Paths start with a /. Eg. /1XS1oqL89opfnbLl8WnZY1O1uJx.jpg
+  fn image_url(&self, path: Option<String>) -> Option<String> {
+    path
+      .filter(|path| !path.is_empty())
+      .map(|path| format!("{}{path}", self.image_base_url))
+  }
+
+  async fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, &str)]) -> Result<T, RenamerError> {
+    let url = format!("{}{path}", self.base_url);
+    let response = self.http.get(&url, query, |request| self.authenticate(request)).await?;
+
+    decode_json_response(&url, response)
+  }
+
+  // Read access tokens are JWTs
+  fn authenticate(&self, request: RequestBuilder) -> RequestBuilder {
+    if self.api_key.starts_with("eyJ") {
+      request.bearer_auth(&self.api_key)
+    } else {
+      request.query(&[("api_key", &self.api_key)])
+    }
+  }
+}
+
+impl MetadataProvider for TmdbApiClient {
+  async fn series_season(&self, url: &str) -> Result<EpisodesDefinition, RenamerError> {
+    let season_url = TmdbSeasonUrl::parse(url)?;
+    let (series, translated_series): (ApiSeries, _) = self.get_translated(&format!("/tv/{}", season_url.id)).await?;
+    let (season, translated_season): (ApiSeason, _) = self.get_translated(&format!("/tv/{}/season/{}", season_url.id, season_url.season)).await?;
+    let translated_episodes = translated_season.map(|s| s.episodes).unwrap_or_default();
+
+    let episodes =
+      season
+        .episodes
+        .into_iter()
+        .map(|e| {
+          let air_date = e.air_date.and_then(|air_date| air_date.parse::<AirDate>().ok());
+          let translation = translated_episodes.iter().find(|t| t.season_number == e.season_number && t.episode_number == e.episode_number);
+
+          EpisodeNumber::from_numbers(e.season_number, e.episode_number)
+            .map(|number| {
+              EpisodeDefinition {
+                number,
+                name: translated(translation.map(|t| t.name.clone()), e.name),
+                absolute_number: None,
+                air_date,
+                runtime: e.runtime.filter(|r| *r > 0),
+                overview: translation.and_then(|t| t.overview.clone()).filter(|o| !o.is_empty()).or(e.overview.filter(|o| !o.is_empty()))
+              }
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| RenamerError::CouldNotDecodeMetadataBody(url.to_owned(), e))?;
+
+    let metadata =
+      SeriesMetaData {
+        name: translated(translated_series.map(|s| s.name), series.name),
+        tvdb_id: None,
+        tmdb_id: Some(series.id.to_string()),
+        imdb_id: None,
+        season_number: SeasonNumber::new(season_url.season).map_err(|_| RenamerError::InvalidTmdbUrl(url.to_owned()))?,
+        order: Some(EpisodeOrder::Aired),
+        language: self.language.clone(),
+        year: year_of(series.first_air_date).and_then(|year| year.parse().ok()),
+      };
+
+    Ok(
+      EpisodesDefinition {
+        metadata,
+        episodes,
+        extras: vec![],
+      }
+    )
+  }
+
+  async fn movie(&self, url: &str) -> Result<MovieMetadata, RenamerError> {
+    let movie_url = TmdbMovieUrl::parse(url)?;
+    let (movie, translated_movie): (ApiMovie, _) = self.get_translated(&format!("/movie/{}", movie_url.id)).await?;
+    let title = translated(translated_movie.map(|m| m.title), movie.title);
+    let year = year_of(movie.release_date).and_then(|year| year.parse().ok());
+    let imdb_id = movie.imdb_id.filter(|id| !id.is_empty());
+
+    let movie_definition =
+      MovieDefinition::from_tmdb(title, movie.id.to_string())
+        .with_language(self.language.clone())
+        .with_year(year)
+        .with_imdb_id(imdb_id);
+
+    Ok(MovieMetadata::Single(movie_definition))
+  }
+
+  async fn season_sources(&self, url: &str) -> Result<Vec<String>, RenamerError> {
+    let series_url = TmdbSeriesUrl::parse(url)?;
+    let series: ApiSeries = self.get(&format!("/tv/{}", series_url.id), &[]).await?;
+
+    Ok(
+      series
+        .seasons
+        .iter()
+        .map(|s| format!("{TMDB_WEBSITE}/tv/{}/season/{}", series.id, s.season_number))
+        .collect()
+    )
+  }
+
+  async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchResult>, RenamerError> {
+    let results = match query.kind {
+      MediaKind::Series => self.search_series(&query.name).await?,
+      MediaKind::Movie => self.search_movie(&query.name, query.year).await?,
+    };
+
+    Ok(
+      results
+        .into_iter()
+        .map(|r| {
+          let source = match query.kind {
+            MediaKind::Series => format!("{TMDB_WEBSITE}/tv/{}/season/{}", r.id, query.season),
+            MediaKind::Movie => format!("{TMDB_WEBSITE}/movie/{}", r.id),
+          };
+
+          SearchResult {
+            id: r.id.to_string(),
+            name: r.name,
+            year: r.year,
+            source
+          }
+        })
+        .collect()
+    )
+  }
+
+  async fn series_artwork(&self, url: &str) -> Result<Artwork, RenamerError> {
+    let season_url = TmdbSeasonUrl::parse(url)?;
+    let series: ApiSeries = self.get(&format!("/tv/{}", season_url.id), &[]).await?;
+    let season: ApiSeason = self.get(&format!("/tv/{}/season/{}", season_url.id, season_url.season), &[]).await?;
+
+    Ok(
+      Artwork {
+        poster: self.image_url(series.poster_path),
+        fanart: self.image_url(series.backdrop_path),
+        season_poster: self.image_url(season.poster_path),
+      }
+    )
+  }
+
+  async fn movie_artwork(&self, url: &str) -> Result<Artwork, RenamerError> {
+    let movie_url = TmdbMovieUrl::parse(url)?;
+    let movie: ApiMovie = self.get(&format!("/movie/{}", movie_url.id), &[]).await?;
+
+    Ok(
+      Artwork {
+        poster: self.image_url(movie.poster_path),
+        fanart: self.image_url(movie.backdrop_path),
+        season_poster: None,
+      }
+    )
+  }
+
+  fn http_client(&self) -> Option<&HttpClient> {
+    Some(&self.http)
+  }
+}
+
+// Dates are in the format: YYYY-MM-DD
+fn year_of(date: Option<String>) -> Option<String> {
+  date
+    .and_then(|d| d.get(0..4).map(|y| y.to_owned()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_urls() {
+      assert_eq!(TmdbSeasonUrl::parse("https://www.themoviedb.org/tv/1399-game-of-thrones/season/2").unwrap(), TmdbSeasonUrl { id: 1399, season: 2 });
+      assert_eq!(TmdbSeasonUrl::parse("https://www.themoviedb.org/tv/1399/season/1?language=en-US").unwrap(), TmdbSeasonUrl { id: 1399, season: 1 });
+      assert_eq!(TmdbMovieUrl::parse("https://www.themoviedb.org/movie/603-the-matrix").unwrap(), TmdbMovieUrl { id: 603 });
+      assert!(TmdbMovieUrl::parse("https://www.themoviedb.org/tv/1399-game-of-thrones").is_err());
+      assert_eq!(TmdbSeriesUrl::parse("https://www.themoviedb.org/tv/1399-game-of-thrones").unwrap(), TmdbSeriesUrl { id: 1399 });
+      assert_eq!(TmdbSeriesUrl::parse("https://www.themoviedb.org/tv/1399-game-of-thrones/season/2").unwrap(), TmdbSeriesUrl { id: 1399 });
+      assert_eq!(TmdbSeriesUrl::parse("1399").unwrap(), TmdbSeriesUrl { id: 1399 })
+    }
+
+    #[tokio::test]
+    async fn test_season_sources() {
+      let mut server = Server::new_async().await;
+
+      let _series =
+        server
+          .mock("GET", "/tv/1399")
+          .match_query(Matcher::UrlEncoded("api_key".into(), "test-key".into()))
+          .with_body(r#"{ "id": 1399, "name": "Game of Thrones", "seasons": [ { "season_number": 0 }, { "season_number": 1 }, { "season_number": 2 } ] }"#)
+          .create_async()
+          .await;
+
+      let client = TmdbApiClient::new(HttpClient::default(), &server.url(), "test-key");
+      let result = client.season_sources("https://www.themoviedb.org/tv/1399-game-of-thrones").await.unwrap();
+
+      let expected =
+        vec![
+          "https://www.themoviedb.org/tv/1399/season/0",
+          "https://www.themoviedb.org/tv/1399/season/1",
+          "https://www.themoviedb.org/tv/1399/season/2",
+        ];
+
+      assert_eq!(result, expected)
+    }
+
+    #[tokio::test]
+    async fn test_series_season() {
+      let mut server = Server::new_async().await;
+
+      let _series =
+        server
+          .mock("GET", "/tv/1399")
+          .match_query(Matcher::UrlEncoded("api_key".into(), "test-key".into()))
+          .with_body(r#"{ "id": 1399, "name": "Game of Thrones", "first_air_date": "2011-04-17" }"#)
+          .create_async()
+          .await;
+
+      let _season =
+        server
+          .mock("GET", "/tv/1399/season/1")
+          .match_query(Matcher::UrlEncoded("api_key".into(), "test-key".into()))
+          .with_body(r#"{
+            "season_number": 1,
+            "episodes": [
+              { "season_number": 1, "episode_number": 1, "name": "Winter Is Coming", "air_date": "2011-04-17", "runtime": 62, "overview": "" },
+              { "season_number": 1, "episode_number": 2, "name": "The Kingsroad" }
+            ]
+          }"#)
+          .create_async()
+          .await;
+
+      let client = TmdbApiClient::new(HttpClient::default(), &server.url(), "test-key");
+      let result = client.series_season("https://www.themoviedb.org/tv/1399-game-of-thrones/season/1").await.unwrap();
+
+      let expected =
+        EpisodesDefinition {
+          metadata: SeriesMetaData {
+            name: "Game of Thrones".to_owned(),
+            tvdb_id: None,
+            tmdb_id: Some("1399".to_owned()),
+            imdb_id: None,
+            season_number: "1".parse().unwrap(),
+            order: Some(EpisodeOrder::Aired),
+            language: None,
+            year: Some(2011)
+          },
+          episodes: vec![
+            EpisodeDefinition { number: "S01E01".parse().unwrap(), name: "Winter Is Coming".to_owned(), absolute_number: None, air_date: Some("2011-04-17".parse().unwrap()), runtime: Some(62), overview: None },
+            EpisodeDefinition { number: "S01E02".parse().unwrap(), name: "The Kingsroad".to_owned(), absolute_number: None, air_date: None, runtime: None, overview: None },
+          ],
+          extras: vec![]
+        };
+
+      assert_eq!(result, expected)
+    }
+
+    #[tokio::test]
+    async fn test_movie_with_read_access_token() {
+      let mut server = Server::new_async().await;
+
+      let _movie =
+        server
+          .mock("GET", "/movie/603")
+          .match_header("authorization", "Bearer eyJtest")
+          .with_body(r#"{ "id": 603, "title": "The Matrix", "release_date": "1999-03-30", "imdb_id": "tt0133093" }"#)
+          .create_async()
+          .await;
+
+      let client = TmdbApiClient::new(HttpClient::default(), &server.url(), "eyJtest");
+      let result = client.movie("https://www.themoviedb.org/movie/603-the-matrix").await.unwrap();
+
+      assert_eq!(result, MovieMetadata::Single(MovieDefinition::from_tmdb("The Matrix".to_owned(), "603".to_owned()).with_year(Some(1999)).with_imdb_id(Some("tt0133093".to_owned()))))
+    }
+
+    #[tokio::test]
+    async fn test_movie_with_language() {
+      let mut server = Server::new_async().await;
+
+      let _movie =
+        server
+          .mock("GET", "/movie/603")
+          .match_query(Matcher::Exact("api_key=test-key".into()))
+          .with_body(r#"{ "id": 603, "title": "The Matrix" }"#)
+          .create_async()
+          .await;
+
+      let _translated_movie =
+        server
+          .mock("GET", "/movie/603")
+          .match_query(Matcher::AllOf(vec![Matcher::UrlEncoded("api_key".into(), "test-key".into()), Matcher::UrlEncoded("language".into(), "ja-JP".into())]))
+          .with_body(r#"{ "id": 603, "title": "マトリックス" }"#)
+          .create_async()
+          .await;
+
+      let client = TmdbApiClient::new(HttpClient::default(), &server.url(), "test-key").with_language(Some("ja-JP".to_owned()));
+      let result = client.movie("https://www.themoviedb.org/movie/603-the-matrix").await.unwrap();
+
+      let expected = MovieDefinition::from_tmdb("マトリックス".to_owned(), "603".to_owned()).with_language(Some("ja-JP".to_owned()));
+      assert_eq!(result, MovieMetadata::Single(expected))
+    }
+
+    #[tokio::test]
+    async fn test_search_movie() {
+      let mut server = Server::new_async().await;
+
+      let _search =
+        server
+          .mock("GET", "/search/movie")
+          .match_query(Matcher::AllOf(vec![Matcher::UrlEncoded("query".into(), "The Matrix".into()), Matcher::UrlEncoded("year".into(), "1999".into())]))
+          .with_body(r#"{ "results": [ { "id": 603, "title": "The Matrix", "release_date": "1999-03-30" } ] }"#)
+          .create_async()
+          .await;
+
+      let client = TmdbApiClient::new(HttpClient::default(), &server.url(), "test-key");
+      let query = SearchQuery { kind: MediaKind::Movie, name: "The Matrix".to_owned(), year: Some(1999), season: 1 };
+      let results = client.search(&query).await.unwrap();
+
+      let expected =
+        vec![
+          SearchResult {
+            id: "603".to_owned(),
+            name: "The Matrix".to_owned(),
+            year: Some("1999".to_owned()),
+            source: "https://www.themoviedb.org/movie/603".to_owned()
+          }
+        ];
+
+      assert_eq!(results, expected)
+    }
+
+    #[tokio::test]
+    async fn test_series_artwork() {
+      let mut server = Server::new_async().await;
+
+      let _series =
+        server
+          .mock("GET", "/tv/1399")
+          .match_query(Matcher::UrlEncoded("api_key".into(), "test-key".into()))
+          .with_body(r#"{ "id": 1399, "name": "Game of Thrones", "poster_path": "/poster.jpg", "backdrop_path": "/backdrop.jpg" }"#)
+          .create_async()
+          .await;
+
+      let _season =
+        server
+          .mock("GET", "/tv/1399/season/2")
+          .match_query(Matcher::UrlEncoded("api_key".into(), "test-key".into()))
+          .with_body(r#"{ "episodes": [], "poster_path": "/season-2.jpg" }"#)
+          .create_async()
+          .await;
+
+      let client = TmdbApiClient::new(HttpClient::default(), &server.url(), "test-key").with_image_base_url("https://images.test/original/");
+      let result = client.series_artwork("https://www.themoviedb.org/tv/1399-game-of-thrones/season/2").await.unwrap();
+
+      let expected =
+        Artwork {
+          poster: Some("https://images.test/original/poster.jpg".to_owned()),
+          fanart: Some("https://images.test/original/backdrop.jpg".to_owned()),
+          season_poster: Some("https://images.test/original/season-2.jpg".to_owned()),
+        };
+
+      assert_eq!(result, expected)
+    }
+
+    #[tokio::test]
+    async fn test_movie_artwork_without_backdrop() {
+      let mut server = Server::new_async().await;
+
+      let _movie =
+        server
+          .mock("GET", "/movie/603")
+          .match_query(Matcher::UrlEncoded("api_key".into(), "test-key".into()))
+          .with_body(r#"{ "id": 603, "title": "The Matrix", "poster_path": "/poster.jpg", "backdrop_path": null }"#)
+          .create_async()
+          .await;
+
+      let client = TmdbApiClient::new(HttpClient::default(), &server.url(), "test-key");
+      let result = client.movie_artwork("https://www.themoviedb.org/movie/603-the-matrix").await.unwrap();
+
+      assert_eq!(result, Artwork { poster: Some("https://image.tmdb.org/t/p/original/poster.jpg".to_owned()), fanart: None, season_poster: None })
+    }
+}
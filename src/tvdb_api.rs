@@ -0,0 +1,1028 @@
+use std::sync::Mutex;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use crate::cli::ProviderArgs;
+use crate::http_client::{HttpClient, HttpResponse};
+use crate::metadata_downloader::{decode_json_response, url_path_segments};
+use crate::metadata_provider::{translated, MediaKind, MetadataProvider, SearchQuery, SearchResult};
+use crate::models::{AirDate, Artwork, EpisodeDefinition, EpisodeNumber, EpisodeOrder, EpisodesDefinition, MovieDefinition, MovieMetadata, RenamerError, SeasonNumber, SeriesMetaData};
+
+pub const TVDB_WEBSITE: &str = "https://thetvdb.com";
+
+// Artwork types, from /artwork/types
+const SERIES_BACKGROUND_TYPE: u32 = 3;
+const MOVIE_BACKGROUND_TYPE: u32 = 15;
+
+/// Client for the TVDB v4 JSON API: https://thetvdb.github.io/v4-api/
+///
+/// A login token is requested on first use and reused for subsequent requests.
+/// If the token expires, we login again once and retry the request.
+pub struct TvdbApiClient {
+  http: HttpClient,
+  base_url: String,
+  api_key: String,
+  pin: Option<String>,
+  token: Mutex<Option<String>>,
+  language: Option<String>,
+}
+
+/// The season types supported by TVDB
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeasonType {
+  Official,
+  Dvd,
+  Absolute,
+}
+
+impl SeasonType {
+  pub fn parse(value: &str) -> Option<Self> {
+    match value {
+      "official" | "default" | "aired" => Some(SeasonType::Official),
+      "dvd" => Some(SeasonType::Dvd),
+      "absolute" => Some(SeasonType::Absolute),
+      _ => None
+    }
+  }
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      SeasonType::Official => "official",
+      SeasonType::Dvd => "dvd",
+      SeasonType::Absolute => "absolute",
+    }
+  }
+}
+
+impl From<EpisodeOrder> for SeasonType {
+  fn from(order: EpisodeOrder) -> Self {
+    match order {
+      EpisodeOrder::Aired => SeasonType::Official,
+      EpisodeOrder::Dvd => SeasonType::Dvd,
+      EpisodeOrder::Absolute => SeasonType::Absolute,
+    }
+  }
+}
+
+impl From<SeasonType> for EpisodeOrder {
+  fn from(season_type: SeasonType) -> Self {
+    match season_type {
+      SeasonType::Official => EpisodeOrder::Aired,
+      SeasonType::Dvd => EpisodeOrder::Dvd,
+      SeasonType::Absolute => EpisodeOrder::Absolute,
+    }
+  }
+}
+
+/// A TVDB season URL. Example: https://thetvdb.com/series/thundercats/seasons/official/1
+#[derive(Debug, PartialEq)]
+pub struct TvdbSeasonUrl {
+  pub slug: String,
+  pub season_type: SeasonType,
+  pub season: u32,
+}
+
+impl TvdbSeasonUrl {
+  pub fn parse(url: &str) -> Result<Self, RenamerError> {
+    let invalid_url = || RenamerError::InvalidTvdbUrl(url.to_owned());
+
+    match url_path_segments(url).as_slice() {
+      ["series", slug, "seasons", season_type, season] =>
+        Ok(
+          TvdbSeasonUrl {
+            slug: slug.to_string(),
+            season_type: SeasonType::parse(season_type).ok_or_else(invalid_url)?,
+            season: season.parse().map_err(|_| invalid_url())?,
+          }
+        ),
+      _ => Err(invalid_url())
+    }
+  }
+
+  pub fn to_url(&self) -> String {
+    format!("{TVDB_WEBSITE}/series/{}/seasons/{}/{}", self.slug, self.season_type.as_str(), self.season)
+  }
+}
+
+/// A TVDB series URL, or any of its season URLs. Example: https://thetvdb.com/series/thundercats
+/// A series id can also be used with the API.
+#[derive(Debug, PartialEq)]
+pub enum TvdbSeriesUrl {
+  Slug(String),
+  Id(u64),
+}
+
+impl TvdbSeriesUrl {
+  pub fn parse(url: &str) -> Result<Self, RenamerError> {
+    if let Ok(id) = url.trim().parse() {
+      return Ok(TvdbSeriesUrl::Id(id))
+    }
+
+    match url_path_segments(url).as_slice() {
+      ["series", slug, ..] => Ok(TvdbSeriesUrl::Slug(slug.to_string())),
+      _ => Err(RenamerError::InvalidTvdbUrl(url.to_owned()))
+    }
+  }
+}
+
+/// A TVDB movie URL. Example: https://thetvdb.com/movies/star-wars-rise-of-skywalker
+#[derive(Debug, PartialEq)]
+pub struct TvdbMovieUrl {
+  pub slug: String,
+}
+
+impl TvdbMovieUrl {
+  pub fn parse(url: &str) -> Result<Self, RenamerError> {
+    match url_path_segments(url).as_slice() {
+      ["movies", slug] => Ok(TvdbMovieUrl { slug: slug.to_string() }),
+      _ => Err(RenamerError::InvalidTvdbUrl(url.to_owned()))
+    }
+  }
+}
+
+#[derive(Serialize)]
+struct LoginRequest<'a> {
+  apikey: &'a str,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pin: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct ApiResponse<T> {
+  data: T,
+  #[serde(default)]
+  links: Option<ApiLinks>,
+}
+
+#[derive(Deserialize)]
+struct ApiLinks {
+  next: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LoginData {
+  token: String,
+}
+
+#[derive(Deserialize)]
+struct ApiRecord {
+  id: u64,
+  name: String,
+  year: Option<String>,
+  image: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiSeriesExtended {
+  slug: String,
+  seasons: Vec<ApiSeason>,
+}
+
+#[derive(Deserialize)]
+struct ApiSeason {
+  number: u32,
+  #[serde(rename = "type")]
+  season_type: ApiSeasonType,
+  image: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiArtworks {
+  #[serde(default)]
+  artworks: Vec<ApiArtwork>,
+}
+
+#[derive(Deserialize)]
+struct ApiArtwork {
+  image: String,
+  #[serde(rename = "type")]
+  artwork_type: u32,
+  #[serde(default)]
+  score: f64,
+}
+
+#[derive(Deserialize)]
+struct ApiSeasonType {
+  #[serde(rename = "type")]
+  season_type: String,
+}
+
+#[derive(Deserialize)]
+struct ApiSeriesEpisodes {
+  episodes: Vec<ApiEpisode>,
+}
+
+#[derive(Deserialize)]
+struct ApiTranslation {
+  name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiSearchResult {
+  tvdb_id: String,
+  name: String,
+  slug: String,
+  year: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiEpisode {
+  name: Option<String>,
+  season_number: u32,
+  number: u32,
+  absolute_number: Option<u32>,
+  aired: Option<String>,
+  runtime: Option<u32>,
+  overview: Option<String>,
+}
+
+impl TvdbApiClient {
+  pub fn new(http: HttpClient, base_url: &str, api_key: &str, pin: Option<&str>) -> Self {
+    Self {
+      http,
+      base_url: base_url.trim_end_matches('/').to_owned(),
+      api_key: api_key.to_owned(),
+      pin: pin.map(|p| p.to_owned()),
+      token: Mutex::new(None),
+      language: None,
+    }
+  }
+
+  /// Retrieves names in the language, such as deu, falling back to English for any that are not translated
+  pub fn with_language(self, language: Option<String>) -> Self {
+    Self {
+      language,
+      ..self
+    }
+  }
+
+  pub fn from_args(provider_args: &ProviderArgs) -> Result<Self, RenamerError> {
+    let api_key =
+      provider_args
+        .tvdb_api_key
+        .as_ref()
+        .ok_or(RenamerError::MissingTvdbApiKey)?;
+
+    let http = HttpClient::from_args(&provider_args.http)?;
+    let client = Self::new(http, &provider_args.tvdb_api_url, api_key, provider_args.tvdb_api_pin.as_deref());
+    Ok(client.with_language(provider_args.language.clone()))
+  }
+
+  async fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, &str)]) -> Result<ApiResponse<T>, RenamerError> {
+    let url = format!("{}{path}", self.base_url);
+    let response = self.get_response(&url, query).await?;
+
+    decode_json_response(&url, response)
+  }
+
+  // Translations that don't exist are not found, rather than empty
+  async fn get_translation(&self, path: &str) -> Result<Option<ApiTranslation>, RenamerError> {
+    let url = format!("{}{path}", self.base_url);
+    let response = self.get_response(&url, &[]).await?;
+
+    if response.status == StatusCode::NOT_FOUND {
+      Ok(None)
+    } else {
+      decode_json_response::<ApiResponse<ApiTranslation>>(&url, response).map(|r| Some(r.data))
+    }
+  }
+
+  async fn get_response(&self, url: &str, query: &[(&str, &str)]) -> Result<HttpResponse, RenamerError> {
+    // Cached responses don't need a token, so we don't login when offline
+    if let Some(response) = self.http.cached(url, query)? {
+      return Ok(response)
+    }
+
+    let token = self.token().await?;
+    let response = self.http.get(url, query, |request| request.bearer_auth(&token)).await?;
+
+    // Token has expired. Login again and retry once.
+    if response.status == StatusCode::UNAUTHORIZED {
+      self.clear_token();
+      let token = self.token().await?;
+      self.http.get(url, query, |request| request.bearer_auth(&token)).await
+    } else {
+      Ok(response)
+    }
+  }
+
+  async fn token(&self) -> Result<String, RenamerError> {
+    if let Some(token) = self.token.lock().unwrap().clone() {
+      return Ok(token)
+    }
+
+    let url = format!("{}/login", self.base_url);
+    let login_request = LoginRequest { apikey: &self.api_key, pin: self.pin.as_deref() };
+    let body = serde_json::to_string(&login_request).map_err(|e| RenamerError::CouldNotLoginToTvdbApi(url.clone(), e.to_string()))?;
+
+    let response = self.http.post_json(&url, &body).await?;
+
+    if !response.status.is_success() {
+      return Err(RenamerError::CouldNotLoginToTvdbApi(url, response.status.to_string()))
+    }
+
+    let login: ApiResponse<LoginData> = decode_json_response(&url, response)?;
+    let token = login.data.token;
+    *self.token.lock().unwrap() = Some(token.clone());
+
+    Ok(token)
+  }
+
+  fn clear_token(&self) {
+    *self.token.lock().unwrap() = None
+  }
+
+  // The highest scored artwork of the type, as voted by TVDB users
+  async fn best_artwork(&self, path: &str, artwork_type: u32) -> Result<Option<String>, RenamerError> {
+    let artworks: ApiArtworks = self.get(path, &[]).await?.data;
+
+    Ok(
+      artworks
+        .artworks
+        .into_iter()
+        .filter(|a| a.artwork_type == artwork_type && !a.image.is_empty())
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+        .map(|a| a.image)
+    )
+  }
+
+  // Episodes are paged. Keep requesting pages until there is no next link.
+  async fn season_episodes(&self, path: &str, season: u32) -> Result<Vec<ApiEpisode>, RenamerError> {
+    let mut episodes: Vec<ApiEpisode> = vec![];
+    let mut page = 0;
+
+    loop {
+      let season = season.to_string();
+      let page_number = page.to_string();
+      let response: ApiResponse<ApiSeriesEpisodes> = self.get(path, &[("season", &season), ("page", &page_number)]).await?;
+      episodes.extend(response.data.episodes);
+
+      match response.links.and_then(|l| l.next) {
+        Some(_) => page += 1,
+        None => break
+      }
+    }
+
+    Ok(episodes)
+  }
+}
+
+
+impl MetadataProvider for TvdbApiClient {
+  async fn series_season(&self, url: &str) -> Result<EpisodesDefinition, RenamerError> {
+    let season_url = TvdbSeasonUrl::parse(url)?;
+    let series: ApiRecord = self.get(&format!("/series/slug/{}", season_url.slug), &[]).await?.data;
+
+    let episodes_path = format!("/series/{}/episodes/{}", series.id, season_url.season_type.as_str());
+    let episodes = self.season_episodes(&episodes_path, season_url.season).await?;
+
+    // Episodes in another language are listed separately, and names that are not translated are left empty
+    let (series_name, translated_episodes) =
+      match &self.language {
+        Some(language) => {
+          let translation = self.get_translation(&format!("/series/{}/translations/{language}", series.id)).await?;
+          let translated_episodes = self.season_episodes(&format!("{episodes_path}/{language}"), season_url.season).await?;
+          (translated(translation.and_then(|t| t.name), series.name), translated_episodes)
+        },
+        None => (series.name, vec![])
+      };
+
+    // In the absolute order, the number of each episode is its absolute number
+    let is_absolute_order = season_url.season_type == SeasonType::Absolute;
+
+    let mut episodes =
+      episodes
+        .into_iter()
+        .filter(|e| e.season_number == season_url.season)
+        .map(|e| {
+          let absolute_number = e.absolute_number.filter(|n| *n > 0).or(is_absolute_order.then_some(e.number));
+          let air_date = e.aired.and_then(|aired| aired.parse::<AirDate>().ok());
+          let translation = translated_episodes.iter().find(|t| t.season_number == e.season_number && t.number == e.number);
+
+          EpisodeNumber::from_numbers(e.season_number, e.number)
+            .map(|number| {
+              EpisodeDefinition {
+                number,
+                name: translated(translation.and_then(|t| t.name.clone()), episode_name(e.name, e.number)),
+                absolute_number,
+                air_date,
+                runtime: e.runtime.filter(|r| *r > 0),
+                overview: translation.and_then(|t| t.overview.clone()).filter(|o| !o.is_empty()).or(e.overview.filter(|o| !o.is_empty()))
+              }
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| RenamerError::CouldNotDecodeMetadataBody(url.to_owned(), e))?;
+
+    // Pages are not guaranteed to list episodes in order
+    episodes.sort_by_key(|e| e.number);
+
+    let metadata =
+      SeriesMetaData {
+        name: series_name,
+        tvdb_id: Some(series.id.to_string()),
+        tmdb_id: None,
+        imdb_id: None,
+        season_number: SeasonNumber::new(season_url.season).map_err(|_| RenamerError::InvalidTvdbUrl(url.to_owned()))?,
+        order: Some(season_url.season_type.into()),
+        language: self.language.clone(),
+        year: year_of(series.year.as_deref()),
+      };
+
+    Ok(
+      EpisodesDefinition {
+        metadata,
+        episodes,
+        extras: vec![],
+      }
+    )
+  }
+
+  async fn movie(&self, url: &str) -> Result<MovieMetadata, RenamerError> {
+    let movie_url = TvdbMovieUrl::parse(url)?;
+    let movie: ApiRecord = self.get(&format!("/movies/slug/{}", movie_url.slug), &[]).await?.data;
+    let year = year_of(movie.year.as_deref());
+
+    let name =
+      match &self.language {
+        Some(language) => {
+          let translation = self.get_translation(&format!("/movies/{}/translations/{language}", movie.id)).await?;
+          translated(translation.and_then(|t| t.name), movie.name)
+        },
+        None => movie.name
+      };
+
+    Ok(MovieMetadata::Single(MovieDefinition::new(name, movie.id.to_string()).with_language(self.language.clone()).with_year(year)))
+  }
+
+  // Season urls use the official order. The order of each season can then be chosen with --order.
+  async fn season_sources(&self, url: &str) -> Result<Vec<String>, RenamerError> {
+    let series_id =
+      match TvdbSeriesUrl::parse(url)? {
+        TvdbSeriesUrl::Slug(slug) => self.get::<ApiRecord>(&format!("/series/slug/{slug}"), &[]).await?.data.id,
+        TvdbSeriesUrl::Id(id) => id,
+      };
+
+    let series: ApiSeriesExtended = self.get(&format!("/series/{series_id}/extended"), &[("short", "true")]).await?.data;
+
+    let mut season_numbers =
+      series
+        .seasons
+        .iter()
+        .filter(|s| SeasonType::parse(&s.season_type.season_type) == Some(SeasonType::Official))
+        .map(|s| s.number)
+        .collect::<Vec<_>>();
+
+    season_numbers.sort();
+    season_numbers.dedup();
+
+    Ok(
+      season_numbers
+        .into_iter()
+        .map(|season| TvdbSeasonUrl { slug: series.slug.clone(), season_type: SeasonType::Official, season }.to_url())
+        .collect()
+    )
+  }
+
+  async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchResult>, RenamerError> {
+    let search_type = match query.kind {
+      MediaKind::Series => "series",
+      MediaKind::Movie => "movie",
+    };
+
+    let year = query.year.map(|y| y.to_string());
+    let mut params = vec![("query", query.name.as_str()), ("type", search_type)];
+    if let Some(year) = year.as_deref() {
+      params.push(("year", year))
+    }
+
+    let results: Vec<ApiSearchResult> = self.get("/search", &params).await?.data;
+
+    Ok(
+      results
+        .into_iter()
+        .map(|r| {
+          let source = match query.kind {
+            MediaKind::Series => format!("{TVDB_WEBSITE}/series/{}/seasons/official/{}", r.slug, query.season),
+            MediaKind::Movie => format!("{TVDB_WEBSITE}/movies/{}", r.slug),
+          };
+
+          SearchResult {
+            id: r.tvdb_id,
+            name: r.name,
+            year: r.year,
+            source
+          }
+        })
+        .collect()
+    )
+  }
+
+  // Season posters are listed with the seasons of each type, as the orders group episodes differently
+  async fn series_artwork(&self, url: &str) -> Result<Artwork, RenamerError> {
+    let season_url = TvdbSeasonUrl::parse(url)?;
+    let series: ApiRecord = self.get(&format!("/series/slug/{}", season_url.slug), &[]).await?.data;
+    let extended: ApiSeriesExtended = self.get(&format!("/series/{}/extended", series.id), &[("short", "true")]).await?.data;
+
+    let season_poster =
+      extended
+        .seasons
+        .into_iter()
+        .find(|s| s.number == season_url.season && SeasonType::parse(&s.season_type.season_type) == Some(season_url.season_type))
+        .and_then(|s| s.image);
+
+    Ok(
+      Artwork {
+        poster: series.image,
+        fanart: self.best_artwork(&format!("/series/{}/artworks", series.id), SERIES_BACKGROUND_TYPE).await?,
+        season_poster,
+      }
+      .without_empty()
+    )
+  }
+
+  async fn movie_artwork(&self, url: &str) -> Result<Artwork, RenamerError> {
+    let movie_url = TvdbMovieUrl::parse(url)?;
+    let movie: ApiRecord = self.get(&format!("/movies/slug/{}", movie_url.slug), &[]).await?.data;
+
+    Ok(
+      Artwork {
+        poster: movie.image,
+        fanart: self.best_artwork(&format!("/movies/{}/extended", movie.id), MOVIE_BACKGROUND_TYPE).await?,
+        season_poster: None,
+      }
+      .without_empty()
+    )
+  }
+
+  fn http_client(&self) -> Option<&HttpClient> {
+    Some(&self.http)
+  }
+}
+
+// Years are strings, and are empty when not known
+fn year_of(year: Option<&str>) -> Option<u16> {
+  year.and_then(|year| year.parse().ok())
+}
+
+// Episodes that have not been named yet are given a placeholder, so they still have a file name. Eg. Episode 5
+fn episode_name(name: Option<String>, number: u32) -> String {
+  name
+    .filter(|name| !name.trim().is_empty())
+    .unwrap_or_else(|| format!("Episode {number}"))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use mockito::{Matcher, Server};
+    use tempfile::tempdir;
+    use crate::http_client::ResponseCache;
+    use pretty_assertions::assert_eq;
+
+    const LOGIN_RESPONSE: &str = r#"{ "status": "success", "data": { "token": "test-token" } }"#;
+
+    #[test]
+    fn test_parse_season_url() {
+      let season_url = TvdbSeasonUrl::parse("https://thetvdb.com/series/thundercats/seasons/dvd/2").unwrap();
+
+      let expected =
+        TvdbSeasonUrl {
+          slug: "thundercats".to_owned(),
+          season_type: SeasonType::Dvd,
+          season: 2
+        };
+
+      assert_eq!(season_url, expected);
+      assert!(TvdbSeasonUrl::parse("https://thetvdb.com/series/thundercats").is_err())
+    }
+
+    #[test]
+    fn test_season_url_with_order() {
+      let season_url = TvdbSeasonUrl::parse("https://thetvdb.com/series/firefly/seasons/official/1").unwrap();
+      let dvd_season_url = TvdbSeasonUrl { season_type: EpisodeOrder::Dvd.into(), ..season_url };
+
+      assert_eq!(dvd_season_url.to_url(), "https://thetvdb.com/series/firefly/seasons/dvd/1")
+    }
+
+    #[test]
+    fn test_parse_series_url() {
+      assert_eq!(TvdbSeriesUrl::parse("https://thetvdb.com/series/thundercats").unwrap(), TvdbSeriesUrl::Slug("thundercats".to_owned()));
+      assert_eq!(TvdbSeriesUrl::parse("https://thetvdb.com/series/thundercats/seasons/dvd/2").unwrap(), TvdbSeriesUrl::Slug("thundercats".to_owned()));
+      assert_eq!(TvdbSeriesUrl::parse("70355").unwrap(), TvdbSeriesUrl::Id(70355));
+      assert!(TvdbSeriesUrl::parse("https://thetvdb.com/movies/the-big-lebowski").is_err())
+    }
+
+    #[tokio::test]
+    async fn test_season_sources() {
+      let mut server = Server::new_async().await;
+
+      let _login =
+        server
+          .mock("POST", "/login")
+          .with_body(LOGIN_RESPONSE)
+          .create_async()
+          .await;
+
+      let _series =
+        server
+          .mock("GET", "/series/slug/thundercats")
+          .with_body(r#"{ "data": { "id": 70355, "name": "ThunderCats" } }"#)
+          .create_async()
+          .await;
+
+      let _extended =
+        server
+          .mock("GET", "/series/70355/extended")
+          .match_query(Matcher::UrlEncoded("short".into(), "true".into()))
+          .with_body(r#"{
+            "data": {
+              "slug": "thundercats",
+              "seasons": [
+                { "number": 2, "type": { "type": "official" } },
+                { "number": 1, "type": { "type": "official" } },
+                { "number": 1, "type": { "type": "dvd" } },
+                { "number": 0, "type": { "type": "official" } }
+              ]
+            }
+          }"#)
+          .create_async()
+          .await;
+
+      let client = TvdbApiClient::new(HttpClient::default(), &server.url(), "test-key", None);
+      let result = client.season_sources("https://thetvdb.com/series/thundercats").await.unwrap();
+
+      let expected =
+        vec![
+          "https://thetvdb.com/series/thundercats/seasons/official/0",
+          "https://thetvdb.com/series/thundercats/seasons/official/1",
+          "https://thetvdb.com/series/thundercats/seasons/official/2",
+        ];
+
+      assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test_parse_movie_url() {
+      let movie_url = TvdbMovieUrl::parse("https://thetvdb.com/movies/the-big-lebowski").unwrap();
+      assert_eq!(movie_url, TvdbMovieUrl { slug: "the-big-lebowski".to_owned() })
+    }
+
+    #[tokio::test]
+    async fn test_series_season() {
+      let mut server = Server::new_async().await;
+
+      let login =
+        server
+          .mock("POST", "/login")
+          .match_body(Matcher::Json(serde_json::json!({ "apikey": "test-key" })))
+          .with_body(LOGIN_RESPONSE)
+          .expect(1)
+          .create_async()
+          .await;
+
+      let _series =
+        server
+          .mock("GET", "/series/slug/thundercats")
+          .match_header("authorization", "Bearer test-token")
+          .with_body(r#"{ "data": { "id": 70355, "name": "ThunderCats", "year": "1985" } }"#)
+          .create_async()
+          .await;
+
+      let _first_page =
+        server
+          .mock("GET", "/series/70355/episodes/dvd")
+          .match_query(Matcher::AllOf(vec![Matcher::UrlEncoded("season".into(), "1".into()), Matcher::UrlEncoded("page".into(), "0".into())]))
+          .with_body(r#"{
+            "data": {
+              "episodes": [
+                { "name": "Exodus", "seasonNumber": 1, "number": 1, "absoluteNumber": 1, "aired": "1985-01-23", "runtime": 22, "overview": "The ThunderCats flee Thundera." },
+                { "name": "The Unholy Alliance", "seasonNumber": 1, "number": 2, "absoluteNumber": 0 }
+              ]
+            },
+            "links": { "next": "page=1" }
+          }"#)
+          .create_async()
+          .await;
+
+      let _second_page =
+        server
+          .mock("GET", "/series/70355/episodes/dvd")
+          .match_query(Matcher::AllOf(vec![Matcher::UrlEncoded("season".into(), "1".into()), Matcher::UrlEncoded("page".into(), "1".into())]))
+          .with_body(r#"{
+            "data": {
+              "episodes": [
+                { "name": "Berbils", "seasonNumber": 1, "number": 3 }
+              ]
+            },
+            "links": { "next": null }
+          }"#)
+          .create_async()
+          .await;
+
+      let client = TvdbApiClient::new(HttpClient::default(), &server.url(), "test-key", None);
+      let result = client.series_season("https://thetvdb.com/series/thundercats/seasons/dvd/1").await.unwrap();
+
+      let expected =
+        EpisodesDefinition {
+          metadata: SeriesMetaData {
+            name: "ThunderCats".to_owned(),
+            tvdb_id: Some("70355".to_owned()),
+            tmdb_id: None,
+            imdb_id: None,
+            season_number: "1".parse().unwrap(),
+            order: Some(EpisodeOrder::Dvd),
+            language: None,
+            year: Some(1985)
+          },
+          episodes: vec![
+            EpisodeDefinition { number: "S01E01".parse().unwrap(), name: "Exodus".to_owned(), absolute_number: Some(1), air_date: Some("1985-01-23".parse().unwrap()), runtime: Some(22), overview: Some("The ThunderCats flee Thundera.".to_owned()) },
+            EpisodeDefinition { number: "S01E02".parse().unwrap(), name: "The Unholy Alliance".to_owned(), absolute_number: None, air_date: None, runtime: None, overview: None },
+            EpisodeDefinition { number: "S01E03".parse().unwrap(), name: "Berbils".to_owned(), absolute_number: None, air_date: None, runtime: None, overview: None },
+          ],
+          extras: vec![]
+        };
+
+      assert_eq!(result, expected);
+      login.assert_async().await
+    }
+
+    #[tokio::test]
+    async fn test_series_season_sorts_and_names_episodes() {
+      let mut server = Server::new_async().await;
+
+      let _login =
+        server
+          .mock("POST", "/login")
+          .with_body(LOGIN_RESPONSE)
+          .create_async()
+          .await;
+
+      let _series =
+        server
+          .mock("GET", "/series/slug/thundercats")
+          .with_body(r#"{ "data": { "id": 70355, "name": "ThunderCats" } }"#)
+          .create_async()
+          .await;
+
+      let _episodes =
+        server
+          .mock("GET", "/series/70355/episodes/official")
+          .match_query(Matcher::Any)
+          .with_body(r#"{
+            "data": {
+              "episodes": [
+                { "name": "Berbils", "seasonNumber": 1, "number": 3 },
+                { "name": "Exodus", "seasonNumber": 1, "number": 1 },
+                { "name": null, "seasonNumber": 1, "number": 2 }
+              ]
+            },
+            "links": { "next": null }
+          }"#)
+          .create_async()
+          .await;
+
+      let client = TvdbApiClient::new(HttpClient::default(), &server.url(), "test-key", None);
+      let result = client.series_season("https://thetvdb.com/series/thundercats/seasons/official/1").await.unwrap();
+
+      let episodes = result.episodes.iter().map(|e| (e.number.to_string(), e.name.as_str())).collect::<Vec<_>>();
+      assert_eq!(episodes, vec![("S01E01".to_owned(), "Exodus"), ("S01E02".to_owned(), "Episode 2"), ("S01E03".to_owned(), "Berbils")])
+    }
+
+    #[tokio::test]
+    async fn test_movie_logs_in_again_when_token_expires() {
+      let mut server = Server::new_async().await;
+
+      let login =
+        server
+          .mock("POST", "/login")
+          .with_body(LOGIN_RESPONSE)
+          .expect(2)
+          .create_async()
+          .await;
+
+      let _expired =
+        server
+          .mock("GET", "/movies/slug/the-big-lebowski")
+          .with_status(401)
+          .expect(1)
+          .create_async()
+          .await;
+
+      let _movie =
+        server
+          .mock("GET", "/movies/slug/the-big-lebowski")
+          .with_body(r#"{ "data": { "id": 659, "name": "The Big Lebowski", "year": "1998" } }"#)
+          .create_async()
+          .await;
+
+      let client = TvdbApiClient::new(HttpClient::default(), &server.url(), "test-key", None);
+      let result = client.movie("https://thetvdb.com/movies/the-big-lebowski").await.unwrap();
+
+      assert_eq!(result, MovieMetadata::Single(MovieDefinition::new("The Big Lebowski".to_owned(), "659".to_owned()).with_year(Some(1998))));
+      login.assert_async().await
+    }
+
+    #[tokio::test]
+    async fn test_series_season_with_language() {
+      let mut server = Server::new_async().await;
+
+      let _login = server.mock("POST", "/login").with_body(LOGIN_RESPONSE).create_async().await;
+      let _series = server.mock("GET", "/series/slug/thundercats").with_body(r#"{ "data": { "id": 70355, "name": "ThunderCats" } }"#).create_async().await;
+
+      let _translation =
+        server
+          .mock("GET", "/series/70355/translations/deu")
+          .with_status(404)
+          .create_async()
+          .await;
+
+      let _episodes =
+        server
+          .mock("GET", "/series/70355/episodes/official")
+          .match_query(Matcher::Any)
+          .with_body(r#"{
+            "data": {
+              "episodes": [
+                { "name": "Exodus", "seasonNumber": 1, "number": 1, "overview": "The ThunderCats flee Thundera." },
+                { "name": "The Unholy Alliance", "seasonNumber": 1, "number": 2 }
+              ]
+            }
+          }"#)
+          .create_async()
+          .await;
+
+      let _translated_episodes =
+        server
+          .mock("GET", "/series/70355/episodes/official/deu")
+          .match_query(Matcher::Any)
+          .with_body(r#"{
+            "data": {
+              "episodes": [
+                { "name": "Der Exodus", "seasonNumber": 1, "number": 1 },
+                { "name": null, "seasonNumber": 1, "number": 2 }
+              ]
+            }
+          }"#)
+          .create_async()
+          .await;
+
+      let cache_dir = tempdir().unwrap();
+      let http = |offline| HttpClient::new(Duration::from_secs(5), Some(ResponseCache::new(cache_dir.path().to_owned(), Duration::from_secs(60))), offline).unwrap();
+
+      let client = TvdbApiClient::new(http(false), &server.url(), "test-key", None).with_language(Some("deu".to_owned()));
+      let result = client.series_season("https://thetvdb.com/series/thundercats/seasons/official/1").await.unwrap();
+
+      // The missing series translation is cached, so it is known to be missing offline
+      let offline_client = TvdbApiClient::new(http(true), &server.url(), "test-key", None).with_language(Some("deu".to_owned()));
+      let offline_result = offline_client.series_season("https://thetvdb.com/series/thundercats/seasons/official/1").await.unwrap();
+      assert_eq!(offline_result, result);
+
+      let names = result.episodes.iter().map(|e| e.name.as_str()).collect::<Vec<_>>();
+
+      assert_eq!(result.metadata.name, "ThunderCats");
+      assert_eq!(result.metadata.language, Some("deu".to_owned()));
+      assert_eq!(names, vec!["Der Exodus", "The Unholy Alliance"]);
+      assert_eq!(result.episodes[0].overview, Some("The ThunderCats flee Thundera.".to_owned()))
+    }
+
+    #[tokio::test]
+    async fn test_search_series() {
+      let mut server = Server::new_async().await;
+
+      let _login =
+        server
+          .mock("POST", "/login")
+          .with_body(LOGIN_RESPONSE)
+          .create_async()
+          .await;
+
+      let _search =
+        server
+          .mock("GET", "/search")
+          .match_query(Matcher::AllOf(vec![Matcher::UrlEncoded("query".into(), "Band of Brothers".into()), Matcher::UrlEncoded("type".into(), "series".into())]))
+          .with_body(r#"{ "data": [ { "tvdb_id": "74205", "name": "Band of Brothers", "slug": "band-of-brothers", "year": "2001" } ] }"#)
+          .create_async()
+          .await;
+
+      let client = TvdbApiClient::new(HttpClient::default(), &server.url(), "test-key", None);
+      let query = SearchQuery { kind: MediaKind::Series, name: "Band of Brothers".to_owned(), year: None, season: 1 };
+      let results = client.search(&query).await.unwrap();
+
+      let expected =
+        vec![
+          SearchResult {
+            id: "74205".to_owned(),
+            name: "Band of Brothers".to_owned(),
+            year: Some("2001".to_owned()),
+            source: "https://thetvdb.com/series/band-of-brothers/seasons/official/1".to_owned()
+          }
+        ];
+
+      assert_eq!(results, expected)
+    }
+
+    #[tokio::test]
+    async fn test_failed_login() {
+      let mut server = Server::new_async().await;
+
+      let _login =
+        server
+          .mock("POST", "/login")
+          .with_status(401)
+          .create_async()
+          .await;
+
+      let client = TvdbApiClient::new(HttpClient::default(), &server.url(), "bad-key", None);
+      let result = client.movie("https://thetvdb.com/movies/the-big-lebowski").await;
+
+      assert!(matches!(result, Err(RenamerError::CouldNotLoginToTvdbApi(_, _))))
+    }
+
+    #[tokio::test]
+    async fn test_series_artwork() {
+      let mut server = Server::new_async().await;
+
+      let _login = server.mock("POST", "/login").with_body(LOGIN_RESPONSE).create_async().await;
+      let _series =
+        server
+          .mock("GET", "/series/slug/thundercats")
+          .with_body(r#"{ "data": { "id": 70355, "name": "ThunderCats", "image": "https://artworks.test/posters/70355.jpg" } }"#)
+          .create_async()
+          .await;
+
+      let _extended =
+        server
+          .mock("GET", "/series/70355/extended")
+          .match_query(Matcher::UrlEncoded("short".into(), "true".into()))
+          .with_body(r#"{
+            "data": {
+              "slug": "thundercats",
+              "seasons": [
+                { "number": 1, "type": { "type": "official" }, "image": "https://artworks.test/seasons/official-1.jpg" },
+                { "number": 1, "type": { "type": "dvd" }, "image": "https://artworks.test/seasons/dvd-1.jpg" },
+                { "number": 2, "type": { "type": "dvd" }, "image": null }
+              ]
+            }
+          }"#)
+          .create_async()
+          .await;
+
+      let _artworks =
+        server
+          .mock("GET", "/series/70355/artworks")
+          .with_body(r#"{
+            "data": {
+              "artworks": [
+                { "image": "https://artworks.test/backgrounds/low.jpg", "type": 3, "score": 10 },
+                { "image": "https://artworks.test/backgrounds/high.jpg", "type": 3, "score": 100 },
+                { "image": "https://artworks.test/banners/banner.jpg", "type": 1, "score": 500 }
+              ]
+            }
+          }"#)
+          .create_async()
+          .await;
+
+      let client = TvdbApiClient::new(HttpClient::default(), &server.url(), "test-key", None);
+
+      let result = client.series_artwork("https://thetvdb.com/series/thundercats/seasons/dvd/1").await.unwrap();
+      let expected =
+        Artwork {
+          poster: Some("https://artworks.test/posters/70355.jpg".to_owned()),
+          fanart: Some("https://artworks.test/backgrounds/high.jpg".to_owned()),
+          season_poster: Some("https://artworks.test/seasons/dvd-1.jpg".to_owned()),
+        };
+      assert_eq!(result, expected);
+
+      let result = client.series_artwork("https://thetvdb.com/series/thundercats/seasons/dvd/2").await.unwrap();
+      assert_eq!(result.season_poster, None)
+    }
+
+    #[tokio::test]
+    async fn test_movie_artwork() {
+      let mut server = Server::new_async().await;
+
+      let _login = server.mock("POST", "/login").with_body(LOGIN_RESPONSE).create_async().await;
+      let _movie =
+        server
+          .mock("GET", "/movies/slug/the-big-lebowski")
+          .with_body(r#"{ "data": { "id": 659, "name": "The Big Lebowski", "image": "" } }"#)
+          .create_async()
+          .await;
+
+      let _extended =
+        server
+          .mock("GET", "/movies/659/extended")
+          .with_body(r#"{ "data": { "artworks": [ { "image": "https://artworks.test/movies/659/backgrounds/1.jpg", "type": 15, "score": 1 } ] } }"#)
+          .create_async()
+          .await;
+
+      let client = TvdbApiClient::new(HttpClient::default(), &server.url(), "test-key", None);
+      let result = client.movie_artwork("https://thetvdb.com/movies/the-big-lebowski").await.unwrap();
+
+      assert_eq!(result, Artwork { poster: None, fanart: Some("https://artworks.test/movies/659/backgrounds/1.jpg".to_owned()), season_poster: None })
+    }
+}
@@ -4,7 +4,10 @@ use crate::cli::*;
 mod series;
 mod movie;
 mod common;
+mod artwork;
 mod export_workflow;
+mod search_workflow;
+mod validate_workflow;
 
 pub use series::series_workflow as series_workflow;
 pub use movie::movie_workflow as movie_workflow;
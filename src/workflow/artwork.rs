@@ -0,0 +1,190 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use console::Style;
+use reqwest::Url;
+use crate::http_client::HttpClient;
+use crate::models::*;
+
+// The names Kodi, Jellyfin and Plex look for in series and movie directories
+const POSTER: &str = "poster";
+const FANART: &str = "fanart";
+const SPECIALS_POSTER: &str = "season-specials-poster";
+
+// Images are saved with the extension of their url, or of their content type when the url has none
+const IMAGE_EXTENSIONS: [&str; 4] = ["jpg", "jpeg", "png", "webp"];
+const DEFAULT_IMAGE_EXTENSION: &str = "jpg";
+
+/// An image to download, along with where to save it. The extension is added once the image is downloaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtworkFile {
+  pub url: String,
+  pub path: PathBuf,
+}
+
+/// Series artwork is shared by every season, so season posters are saved alongside it. Eg. season01-poster
+pub fn series_artwork_files(artwork: &Artwork, series_directory: &Path, season_number: SeasonNumber) -> Vec<ArtworkFile> {
+  let season_poster_name =
+    match season_number.value() {
+      0 => SPECIALS_POSTER.to_owned(),
+      season => format!("season{season:02}-poster"),
+    };
+
+  [
+    (&artwork.poster, POSTER.to_owned()),
+    (&artwork.fanart, FANART.to_owned()),
+    (&artwork.season_poster, season_poster_name),
+  ]
+  .into_iter()
+  .filter_map(|(url, name)| url.clone().map(|url| ArtworkFile { url, path: series_directory.join(name) }))
+  .collect()
+}
+
+pub fn movie_artwork_files(artwork: &Artwork, movie_directory: &Path) -> Vec<ArtworkFile> {
+  [(&artwork.poster, POSTER), (&artwork.fanart, FANART)]
+    .into_iter()
+    .filter_map(|(url, name)| url.clone().map(|url| ArtworkFile { url, path: movie_directory.join(name) }))
+    .collect()
+}
+
+/// Downloads each image that has not already been saved, such as for an earlier season.
+/// Files have already been renamed by then, so images that can't be saved are shown as warnings and returned.
+pub async fn download_artwork(http: &HttpClient, artwork_files: &[ArtworkFile]) -> Vec<RenamerError> {
+  let dim = Style::new().dim();
+  let yellow = Style::new().yellow();
+  let mut failures = vec![];
+
+  for artwork_file in artwork_files {
+    if let Some(existing) = existing_image(&artwork_file.path) {
+      println!("{}", dim.apply_to(format!("Kept existing artwork: {}", existing.to_string_lossy())));
+      continue
+    }
+
+    match save_image(http, artwork_file).await {
+      Ok(path) => println!("{}", dim.apply_to(format!("Saved artwork: {}", path.to_string_lossy()))),
+      Err(e) => {
+        println!("{} {}", yellow.apply_to("Warning:"), e);
+        failures.push(e)
+      }
+    }
+  }
+
+  failures
+}
+
+async fn save_image(http: &HttpClient, artwork_file: &ArtworkFile) -> Result<PathBuf, RenamerError> {
+  let download = http.download(&artwork_file.url).await?;
+
+  let extension =
+    url_extension(&artwork_file.url)
+      .or_else(|| download.content_type.as_deref().and_then(content_type_extension))
+      .unwrap_or(DEFAULT_IMAGE_EXTENSION);
+
+  let path = artwork_file.path.with_extension(extension);
+  fs::write(&path, download.bytes).map_err(|e| RenamerError::CouldNotWriteArtwork(path.clone(), e.to_string()))?;
+
+  Ok(path)
+}
+
+fn existing_image(path: &Path) -> Option<PathBuf> {
+  IMAGE_EXTENSIONS
+    .iter()
+    .map(|extension| path.with_extension(extension))
+    .find(|path| path.exists())
+}
+
+// Eg. https://artworks.thetvdb.com/banners/posters/70355-1.png
+fn url_extension(url: &str) -> Option<&'static str> {
+  let url = Url::parse(url).ok()?;
+  let extension = Path::new(url.path()).extension()?.to_string_lossy().to_lowercase();
+
+  IMAGE_EXTENSIONS.into_iter().find(|image_extension| *image_extension == extension)
+}
+
+// Eg. image/png; charset=binary
+fn content_type_extension(content_type: &str) -> Option<&'static str> {
+  match content_type.split(';').next().unwrap_or_default().trim() {
+    "image/jpeg" => Some("jpg"),
+    "image/png" => Some("png"),
+    "image/webp" => Some("webp"),
+    _ => None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+    use tempfile::tempdir;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_series_artwork_files() {
+      let artwork = Artwork { poster: Some("https://images/poster.jpg".to_owned()), fanart: None, season_poster: Some("https://images/season.jpg".to_owned()) };
+      let series_directory = Path::new("/encodes/tv/Thundercats {tvdb-70355} [tvdbid-70355]");
+
+      assert_eq!(
+        series_artwork_files(&artwork, series_directory, SeasonNumber::new(2).unwrap()),
+        vec![
+          ArtworkFile { url: "https://images/poster.jpg".to_owned(), path: series_directory.join("poster") },
+          ArtworkFile { url: "https://images/season.jpg".to_owned(), path: series_directory.join("season02-poster") },
+        ]
+      );
+
+      let specials = series_artwork_files(&artwork, series_directory, SeasonNumber::new(0).unwrap());
+      assert_eq!(specials[1].path, series_directory.join("season-specials-poster"))
+    }
+
+    #[tokio::test]
+    async fn test_download_skips_existing_files() {
+      let mut server = Server::new_async().await;
+      let dir = tempdir().unwrap();
+
+      let poster = server.mock("GET", "/poster.jpg").with_body([0xFF, 0xD8, 0xFF]).create_async().await;
+      let fanart = server.mock("GET", "/fanart.jpg").expect(0).create_async().await;
+
+      fs::write(dir.path().join("fanart.jpg"), "existing").unwrap();
+
+      let artwork = Artwork { poster: Some(format!("{}/poster.jpg", server.url())), fanart: Some(format!("{}/fanart.jpg", server.url())), season_poster: None };
+      let artwork_files = movie_artwork_files(&artwork, dir.path());
+
+      assert!(download_artwork(&HttpClient::default(), &artwork_files).await.is_empty());
+
+      assert_eq!(fs::read(dir.path().join("poster.jpg")).unwrap(), vec![0xFF, 0xD8, 0xFF]);
+      assert_eq!(fs::read_to_string(dir.path().join("fanart.jpg")).unwrap(), "existing");
+      poster.assert_async().await;
+      fanart.assert_async().await
+    }
+
+    #[tokio::test]
+    async fn test_download_error() {
+      let mut server = Server::new_async().await;
+      let dir = tempdir().unwrap();
+
+      let _poster = server.mock("GET", "/poster.jpg").with_status(404).create_async().await;
+
+      let artwork = Artwork { poster: Some(format!("{}/poster.jpg", server.url())), fanart: None, season_poster: None };
+      let failures = download_artwork(&HttpClient::default(), &movie_artwork_files(&artwork, dir.path())).await;
+
+      assert!(matches!(failures.as_slice(), [RenamerError::CouldNotDownloadArtwork(_, message)] if message.contains("404")));
+      assert!(!dir.path().join("poster.jpg").exists())
+    }
+
+    #[tokio::test]
+    async fn test_download_uses_image_extension() {
+      let mut server = Server::new_async().await;
+      let dir = tempdir().unwrap();
+
+      let _poster = server.mock("GET", "/poster.png").with_body([0x89, 0x50]).create_async().await;
+      let _fanart = server.mock("GET", "/images/fanart").with_header("content-type", "image/webp").with_body([0x52, 0x49]).create_async().await;
+
+      let artwork = Artwork { poster: Some(format!("{}/poster.png", server.url())), fanart: Some(format!("{}/images/fanart", server.url())), season_poster: None };
+      assert!(download_artwork(&HttpClient::default(), &movie_artwork_files(&artwork, dir.path())).await.is_empty());
+
+      assert!(dir.path().join("poster.png").exists());
+      assert!(dir.path().join("fanart.webp").exists());
+
+      // An image saved with any extension is kept
+      assert!(download_artwork(&HttpClient::default(), &movie_artwork_files(&artwork, dir.path())).await.is_empty());
+      assert!(!dir.path().join("poster.jpg").exists())
+    }
+}
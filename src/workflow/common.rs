@@ -1,24 +1,15 @@
-use serde::Deserialize;
 use walkdir::WalkDir;
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, Write};
 use console::Style;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use crate::models::*;
-use crate::cli::*;
 
 pub const ENCODES_FILE: &str = "encode_dir.txt";
 
-pub fn get_metadata_type(input_type: &MetadataInputType) -> ConfigMetadataInputType {
-  match (input_type.clone().url_metadata, input_type.clone().file_metadata) {
-    (Some(url), _) => ConfigMetadataInputType::Url(url),
-    (_, Some(file)) => ConfigMetadataInputType::File(file),
-    _ => ConfigMetadataInputType::Invalid
-  }
-}
-
-
-pub fn write_encodes_file<P: AsRef<Path>>(rename_dir: &RipsSessionRenamesDir, encoded_series_directory_path: P) -> R {
+/// Writes each encode directory on its own line
+pub fn write_encodes_file<P: AsRef<Path>>(rename_dir: &RipsSessionRenamesDir, encoded_directory_paths: &[P]) -> R {
   let encodes_file = rename_dir.as_ref().join(ENCODES_FILE);
   let encodes_file_path = encodes_file.as_path();
 
@@ -31,14 +22,25 @@ pub fn write_encodes_file<P: AsRef<Path>>(rename_dir: &RipsSessionRenamesDir, en
     .open(encodes_file_path)
     .map_err(|e| RenamerError::CouldNotOpenEncodesFile(encodes_file.clone(), e.to_string()))
     .and_then(|mut file| {
-      file.write(encoded_series_directory_path.as_ref().to_string_lossy().as_bytes())
+      let encoded_directories =
+        encoded_directory_paths
+          .iter()
+          .map(|p| p.as_ref().to_string_lossy().to_string())
+          .collect::<Vec<_>>()
+          .join("\n");
+
+      file.write(encoded_directories.as_bytes())
         .and(file.flush())
         .map_err(|e| RenamerError::CouldNotWriteEncodesFile(encodes_file, e.to_string()))
     })
     .map(|_| ())
 }
 
+/// Finds the files ripped into each disc directory of the session.
+/// When a disc directory has a MakeMKV info log, each file is given the title it was ripped from.
 pub fn get_ripped_filenames(rips_session_number: &RipsSessionNumberDir) -> Vec<FileNameAndExt> {
+  let mut disc_infos: HashMap<PathBuf, Option<DiscInfo>> = HashMap::new();
+
   WalkDir::new(rips_session_number)
       .into_iter()
       .filter_map(|re| re.ok())
@@ -46,7 +48,8 @@ pub fn get_ripped_filenames(rips_session_number: &RipsSessionNumberDir) -> Vec<F
         let p = dir_entry.path();
         let is_file = p.is_file();
         let has_disk_subdirectory = p.to_string_lossy().to_string().contains("/disc");
-        if is_file && has_disk_subdirectory {
+        let is_disc_info = p.file_name().is_some_and(|name| name == DISC_INFO_FILE);
+        if is_file && has_disk_subdirectory && !is_disc_info {
           p.file_name().and_then(|name|{
             p.extension().map(|ext| FileNameAndExt::new(p, name, ext))  // Some(FileNameAndExt)
           })
@@ -54,22 +57,61 @@ pub fn get_ripped_filenames(rips_session_number: &RipsSessionNumberDir) -> Vec<F
           None
         }
      })
+    .map(|fne| {
+      let disc_info =
+        fne
+          .path
+          .parent()
+          .and_then(|disc_dir| {
+            disc_infos
+              .entry(disc_dir.to_path_buf())
+              .or_insert_with(|| read_disc_info(disc_dir))
+              .as_ref()
+          });
+
+      let title_info = disc_info.and_then(|disc_info| disc_info.title(&fne.file_name)).cloned();
+      fne.with_title_info(title_info)
+    })
     .collect()
 }
 
+// Discs ripped without saving the info log have no title information
+fn read_disc_info(disc_dir: &Path) -> Option<DiscInfo> {
+  fs::read_to_string(disc_dir.join(DISC_INFO_FILE))
+    .ok()
+    .map(|content| DiscInfo::parse(&content))
+}
+
 
 
-pub fn confirm_changes(files_to_rename: &Vec<Rename>, encodes_series_folder_structure: &Path) -> RenamesResult {
+pub fn confirm_changes<P: AsRef<Path>>(files_to_rename: &Vec<Rename>, encodes_folder_structures: &[P]) -> RenamesResult {
   println!("The following renames will be performed:");
   let yellow = Style::new().yellow();
 
+  let dim = Style::new().dim();
+
   for f in files_to_rename {
-    println!("{:?} -> {:?}", f.from_file_name, yellow.apply_to(f.to_file_name.as_path().to_string_lossy()))
+    println!("{:?} -> {:?}", f.from_file_name, yellow.apply_to(f.to_file_name.as_path().to_string_lossy()));
+
+    for detail in &f.details {
+      println!("    {}", dim.apply_to(detail))
+    }
+
+    for warning in &f.warnings {
+      println!("    {} {}", yellow.apply_to("Warning:"), warning)
+    }
   }
   println!();
 
-  println!("The following directory will be created:");
-  println!("{}", yellow.apply_to(encodes_series_folder_structure.to_string_lossy().to_string()));
+  if encodes_folder_structures.len() == 1 {
+    println!("The following directory will be created:");
+  } else {
+    println!("The following directories will be created:");
+  }
+
+  for d in encodes_folder_structures {
+    println!("{}", yellow.apply_to(d.as_ref().to_string_lossy().to_string()));
+  }
   println!();
 
   println!("Proceed? 'y' to proceed or any other key to abort");
@@ -100,23 +142,42 @@ pub fn create_all_directories(p: &Path) -> R {
 }
 
 
-pub fn perform_rename(renames: &[Rename]) {
-  for r in renames {
-    fs::rename(&r.from_file_name, &r.to_file_name).unwrap_or_else(|e| panic!("could not rename {:?} -> {:?}, due to: {}", &r.from_file_name, &r.to_file_name, e))
+// Fails if two files would be renamed to the same path, as the second rename would replace the first file
+pub fn check_duplicate_targets(renames: &[Rename]) -> R {
+  let mut targets = HashSet::new();
+
+  match renames.iter().find(|r| !targets.insert(&r.to_file_name)) {
+    Some(duplicate) => Err(RenamerError::DuplicateRenameTarget(duplicate.to_file_name.clone())),
+    None => Ok(())
   }
 }
 
-pub fn read_input_from_file<P: AsRef<Path>, R: for<'a> Deserialize<'a>>(path: P) -> Result<R, RenamerError> {
-  let file =
-    fs::File::open(&path)
-      .map_err(|e| RenamerError::CouldNotAccessMetadataFile(path.as_ref().to_string_lossy().to_string(), e.to_string()))?;
 
-  let reader = BufReader::new(file);
-  let u =
-    serde_json::from_reader(reader)
-      .map_err(|e| RenamerError::CouldNotDecodeMetadataFileJson(path.as_ref().to_owned(), e.to_string()))?;
+// The directories are created before any file is renamed, so a failure leaves the ripped files where they were
+pub fn perform_rename(renames: &[Rename]) -> R {
+  // Extras are renamed into sub directories of the renames directory
+  let parents: HashSet<_> = renames.iter().filter_map(|r| r.to_file_name.parent()).collect();
+
+  for parent in parents {
+    fs::create_dir_all(parent).map_err(|e| RenamerError::CouldNotCreateRenamesDirectory(parent.to_owned(), e.to_string()))?
+  }
 
-  Ok(u)
+  for r in renames {
+    fs::rename(&r.from_file_name, &r.to_file_name).unwrap_or_else(|e| panic!("could not rename {:?} -> {:?}, due to: {}", &r.from_file_name, &r.to_file_name, e))
+  }
+
+  Ok(())
+}
+
+/// Combines metadata retrieval errors with a missing processing directory
+pub fn with_processing_dir<T>(metadata: Result<T, RenamerError>, processing_dir: &ProcessingDir) -> Result<T, RenamerError> {
+  let processing_dir_path = processing_dir.as_ref();
+  match (metadata, processing_dir_path.exists()) {
+    (Ok(metadata), true) => Ok(metadata),
+    (Err(RenamerError::MetadataDirectoryDoesNotExist(metadata_path)), false) => Err(RenamerError::ProcessingDirAndMetadaPathDoesNotExit(processing_dir_path.to_owned(), metadata_path)),
+    (Err(e), _) => Err(e),
+    (Ok(_), false) => Err(RenamerError::ProcessingDirectoryDoesNotExist(processing_dir_path.to_owned())),
+  }
 }
 
 pub fn dump_processing_info(processing_dir: &ProcessingDir, session_number: &SessionNumberDir, verbose: bool) {
@@ -130,3 +191,51 @@ pub fn dump_processing_info(processing_dir: &ProcessingDir, session_number: &Ses
     println!()
   }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_ripped_filenames_with_disc_info() {
+      let session_dir = tempdir().unwrap();
+      let disc1 = session_dir.path().join("disc1");
+      let disc2 = session_dir.path().join("disc2");
+      fs::create_dir_all(&disc1).unwrap();
+      fs::create_dir_all(&disc2).unwrap();
+
+      fs::write(disc1.join(DISC_INFO_FILE), include_str!("../../tests/fixtures/makemkv/makemkv-info.txt")).unwrap();
+      fs::write(disc1.join("title_t01.mkv"), "").unwrap();
+      fs::write(disc2.join("title_t00.mkv"), "").unwrap();
+
+      let mut ripped_filenames = get_ripped_filenames(&RipsSessionNumberDir(session_dir.path().to_path_buf()));
+      ripped_filenames.sort_by(|fne1, fne2| fne1.partial_cmp(fne2).unwrap());
+
+      let file_names = ripped_filenames.iter().map(|fne| fne.file_name.as_str()).collect::<Vec<_>>();
+      assert_eq!(file_names, vec!["title_t01.mkv", "title_t00.mkv"]);
+
+      let title_names = ripped_filenames.iter().map(|fne| fne.title_info.as_ref().and_then(|t| t.name.as_deref())).collect::<Vec<_>>();
+      assert_eq!(title_names, vec![Some("Exodus, Part 1"), None])
+    }
+
+    #[test]
+    fn test_perform_rename_creates_directories_first() {
+      let dir = tempdir().unwrap();
+      let ripped_file = dir.path().join("title_t00.mkv");
+      fs::write(&ripped_file, "").unwrap();
+      // A file where the extras directory should be created
+      fs::write(dir.path().join("Featurettes"), "").unwrap();
+
+      let renames = vec![
+        Rename::new(ripped_file.clone(), dir.path().join("renames").join("S01E01 - Exodus.mkv")),
+        Rename::new(dir.path().join("title_t01.mkv"), dir.path().join("Featurettes").join("Behind the Scenes.mkv")),
+      ];
+
+      let result = perform_rename(&renames);
+      assert!(matches!(result, Err(RenamerError::CouldNotCreateRenamesDirectory(path, _)) if path == dir.path().join("Featurettes")));
+      assert!(ripped_file.exists())
+    }
+}
@@ -1,38 +1,336 @@
-use std::path::PathBuf;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use serde::Serialize;
 
-use crate::metadata_downloader::download_metadata;
+use crate::metadata_provider::{MediaKind, MetadataProvider, Provider};
 use crate::models::*;
 use crate::cli::ExportArgs;
 
-pub async fn perform<I: Serialize, F: FnOnce(&str) -> I>(export_args: ExportArgs, get_metadata: F) -> ROutput {
-  let url = export_args.url_metadata;
-  let export_path = export_args.export_path;
-  handle_url_metadata_export(&url, get_metadata, export_path).await
+/// What to do when the export path already exists
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OverwritePolicy {
+  Refuse,
+  Overwrite,
+  Backup,
 }
 
-async fn handle_url_metadata_export<I: Serialize, F: FnOnce(&str) -> I>(url: &str, get_metadata: F, export_path: PathBuf) -> ROutput {
-  let page_content = download_metadata(url).await?;
+impl OverwritePolicy {
+  fn from_args(export_args: &ExportArgs) -> Self {
+    if export_args.force {
+      OverwritePolicy::Overwrite
+    } else if export_args.backup {
+      OverwritePolicy::Backup
+    } else {
+      OverwritePolicy::Refuse
+    }
+  }
+}
+
+/// Where and how to write an export
+#[derive(Debug, Clone, Copy)]
+struct ExportOptions {
+  policy: OverwritePolicy,
+  format: Option<MetadataFormat>,
+}
+
+impl ExportOptions {
+  fn from_args(export_args: &ExportArgs) -> Self {
+    Self {
+      policy: OverwritePolicy::from_args(export_args),
+      format: export_args.format,
+    }
+  }
+}
+
+pub async fn perform_series<P: MetadataProvider>(export_args: ExportArgs, provider: &P, source: &str) -> ROutput {
+  let episodes_definition = provider.series_season(source).await?;
+  handle_url_metadata_export(source, MediaKind::Series, episodes_definition, &export_args.export_path, ExportOptions::from_args(&export_args))
+}
+
+pub async fn perform_movie<P: MetadataProvider>(export_args: ExportArgs, provider: &P, source: &str) -> ROutput {
+  let movie_metadata = provider.movie(source).await?;
+  handle_url_metadata_export(source, MediaKind::Movie, movie_metadata, &export_args.export_path, ExportOptions::from_args(&export_args))
+}
+
+/// Exports every season of a series, in the order selected with --order
+pub async fn perform_all_seasons(export_args: ExportArgs, provider: &Provider, source: &str) -> ROutput {
+  if export_args.per_season && is_stdout(&export_args.export_path) {
+    return Err(RenamerError::InvalidMetadataConfiguration("--per-season can't be used when exporting to stdout".to_owned()))
+  }
+
+  let seasons = get_all_seasons(provider, source, export_args.provider.order, |url, order| provider.with_order(url, order)).await?;
+  let options = ExportOptions::from_args(&export_args);
+
+  if export_args.per_season {
+    for season in seasons {
+      let export_path = season_export_path(&export_args.export_path, season.metadata.season_number);
+      handle_url_metadata_export(source, MediaKind::Series, season, &export_path, options)?;
+    }
+
+    Ok(Output::Success)
+  } else {
+    handle_url_metadata_export(source, MediaKind::Series, SeasonsDefinition { seasons }, &export_args.export_path, options)
+  }
+}
+
+async fn get_all_seasons<P, F>(provider: &P, source: &str, order: Option<EpisodeOrder>, with_order: F) -> Result<Vec<EpisodesDefinition>, RenamerError>
+  where P: MetadataProvider,
+        F: Fn(&str, Option<EpisodeOrder>) -> Result<String, RenamerError>
+{
+  let mut seasons = vec![];
+
+  for season_source in provider.season_sources(source).await? {
+    let season_source = with_order(&season_source, order)?;
+    seasons.push(provider.series_season(&season_source).await?)
+  }
+
+  Ok(seasons)
+}
 
-  // TODO: Change for movie
-  let episodes_definition = get_metadata(&page_content);
-  use std::fs::OpenOptions;
+// Given: thundercats.json and season 1, returns: thundercats-s01.json
+fn season_export_path(export_path: &Path, season_number: SeasonNumber) -> PathBuf {
+  let stem = export_path.file_stem().unwrap_or_default().to_string_lossy();
+  let season = format!("{:02}", season_number.value());
+
+  let file_name =
+    match export_path.extension() {
+      Some(ext) => format!("{stem}-s{season}.{}", ext.to_string_lossy()),
+      None => format!("{stem}-s{season}"),
+    };
+
+  export_path.with_file_name(file_name)
+}
+
+fn handle_url_metadata_export<I: Serialize>(source: &str, kind: MediaKind, metadata: I, export_path: &Path, options: ExportOptions) -> ROutput {
+  let export_error = |message: String| {
+    match kind {
+      MediaKind::Series => RenamerError::CouldNotExportSeriesMetadata(source.to_owned(), export_path.to_owned(), message),
+      MediaKind::Movie => RenamerError::CouldNotExportMovieMetadata(source.to_owned(), export_path.to_owned(), message),
+    }
+  };
+
+  let content =
+    MetadataFormat::for_path(export_path, options.format)
+      .encode(&metadata)
+      .map_err(export_error)?;
+
+  if is_stdout(export_path) {
+    return writeln!(io::stdout().lock(), "{}", content.trim_end())
+      .map_err(|e| export_error(e.to_string()))
+      .map(|_| Output::ExportedToStdout)
+  }
+
+  let mut file = open_export_file(export_path, options.policy, export_error)?;
+
+  file
+    .write_all(content.as_bytes())
+    .map_err(|e| export_error(e.to_string()))
+    .map(|_| Output::Success)
+}
+
+fn open_export_file<F>(export_path: &Path, policy: OverwritePolicy, export_error: F) -> Result<File, RenamerError>
+  where F: Fn(String) -> RenamerError
+{
+  if policy == OverwritePolicy::Backup && export_path.exists() {
+    fs::rename(export_path, backup_path(export_path))
+      .map_err(|e| RenamerError::CouldNotBackupExportFile(export_path.to_owned(), e.to_string()))?
+  }
 
   OpenOptions::new()
     .write(true)
-    .create_new(true)
-    .open(&export_path)
+    .create(true)
+    .truncate(true)
+    .create_new(policy == OverwritePolicy::Refuse)
+    .open(export_path)
     .map_err(|e| {
-      // TODO: Change for movie
-      RenamerError::CouldNotExportEpisodeMetadata(url.to_owned(), export_path.clone(), e.to_string())
+      if e.kind() == io::ErrorKind::AlreadyExists {
+        RenamerError::ExportFileAlreadyExists(export_path.to_owned())
+      } else {
+        export_error(e.to_string())
+      }
     })
-    .and_then(|file| {
-      // TODO: Change for movie - parameterise
-      serde_json::to_writer_pretty(file, &episodes_definition)
-        .map_err(|e| {
-          // TODO: Change for movie
-          RenamerError::CouldNotExportEpisodeMetadata(url.to_owned(), export_path.clone(), e.to_string())
-        })
-    })
-    .map(|_| Output::Success)
+}
+
+fn is_stdout(export_path: &Path) -> bool {
+  export_path == Path::new("-")
+}
+
+// Given: thundercats.json, returns: thundercats.json.bak
+fn backup_path(export_path: &Path) -> PathBuf {
+  let mut file_name = export_path.file_name().unwrap_or_default().to_owned();
+  file_name.push(".bak");
+  export_path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use tempfile::tempdir;
+    use crate::cli::{MkvCommands, MkvInputType, MkvRenamerArgs};
+    use crate::metadata_provider::{HtmlFileProvider, InMemoryProvider};
+    use pretty_assertions::assert_eq;
+
+    const URL: &str = "https://thetvdb.com/series/thundercats/seasons/official/1";
+
+    fn export_args(export_path: &Path) -> ExportArgs {
+      export_args_with(export_path, &[])
+    }
+
+    fn export_args_with(export_path: &Path, extra_args: &[&str]) -> ExportArgs {
+      let export_path = export_path.to_string_lossy();
+      let args = MkvRenamerArgs::parse_from(["mkv-renamer", "series", "export", "-u", URL, "-e", &export_path].iter().chain(extra_args));
+      match args.commands {
+        MkvInputType::Series(MkvCommands::Export(export_args)) => export_args,
+        _ => unreachable!()
+      }
+    }
+
+    #[tokio::test]
+    async fn test_series_export() {
+      let export_dir = tempdir().unwrap();
+      let export_path = export_dir.path().join("series.json");
+
+      let episodes_definition =
+        EpisodesDefinition {
+          metadata: SeriesMetaData {
+            name: "Thundercats".to_owned(),
+            tvdb_id: Some("70355".to_owned()),
+            tmdb_id: None,
+            imdb_id: None,
+            season_number: "1".parse().unwrap(),
+            order: None,
+            language: None,
+            year: None
+          },
+          episodes: vec![
+            EpisodeDefinition { number: "S01E01".parse().unwrap(), name: "Exodus".to_owned(), absolute_number: None, air_date: None, runtime: None, overview: None }
+          ],
+          extras: vec![]
+        };
+
+      let provider = InMemoryProvider { series: Some(episodes_definition.clone()), ..Default::default() };
+      perform_series(export_args(&export_path), &provider, URL).await.unwrap();
+
+      let exported: EpisodesDefinition = serde_json::from_str(&std::fs::read_to_string(&export_path).unwrap()).unwrap();
+      assert_eq!(exported, episodes_definition)
+    }
+
+    #[tokio::test]
+    async fn test_movie_export() {
+      let export_dir = tempdir().unwrap();
+      let export_path = export_dir.path().join("movie.json");
+
+      let movie_metadata = MovieMetadata::Single(MovieDefinition::new("The Big Lebowski".to_owned(), "659".to_owned()));
+
+      let provider = InMemoryProvider { movie: Some(movie_metadata.clone()), ..Default::default() };
+      perform_movie(export_args(&export_path), &provider, URL).await.unwrap();
+
+      let exported = MovieMetadata::decode(MetadataFormat::Json, &std::fs::read_to_string(&export_path).unwrap()).unwrap();
+      assert_eq!(exported, movie_metadata)
+    }
+
+    #[tokio::test]
+    async fn test_export_overwrite_policy() {
+      let export_dir = tempdir().unwrap();
+      let export_path = export_dir.path().join("movie.json");
+      std::fs::write(&export_path, "previous").unwrap();
+
+      let movie_metadata = MovieMetadata::Single(MovieDefinition::new("The Big Lebowski".to_owned(), "659".to_owned()));
+      let provider = InMemoryProvider { movie: Some(movie_metadata.clone()), ..Default::default() };
+
+      let result = perform_movie(export_args(&export_path), &provider, URL).await;
+      assert!(matches!(result, Err(RenamerError::ExportFileAlreadyExists(_))));
+      assert_eq!(std::fs::read_to_string(&export_path).unwrap(), "previous");
+
+      perform_movie(export_args_with(&export_path, &["--backup"]), &provider, URL).await.unwrap();
+      assert_eq!(std::fs::read_to_string(export_dir.path().join("movie.json.bak")).unwrap(), "previous");
+
+      std::fs::write(&export_path, "{}").unwrap();
+      perform_movie(export_args_with(&export_path, &["--force"]), &provider, URL).await.unwrap();
+
+      let exported = MovieMetadata::decode(MetadataFormat::Json, &std::fs::read_to_string(&export_path).unwrap()).unwrap();
+      assert_eq!(exported, movie_metadata)
+    }
+
+    #[tokio::test]
+    async fn test_series_export_formats() {
+      let export_dir = tempdir().unwrap();
+      let yaml_path = export_dir.path().join("series.yaml");
+      let csv_path = export_dir.path().join("series.txt");
+
+      let provider = InMemoryProvider { series: Some(season("1")), ..Default::default() };
+      perform_series(export_args(&yaml_path), &provider, URL).await.unwrap();
+      perform_series(export_args_with(&csv_path, &["--format", "csv"]), &provider, URL).await.unwrap();
+
+      let exported_yaml: EpisodesDefinition = serde_yaml::from_str(&std::fs::read_to_string(&yaml_path).unwrap()).unwrap();
+      assert_eq!(exported_yaml, season("1"));
+
+      let exported_csv: EpisodesDefinition = MetadataFormat::Csv.decode(&std::fs::read_to_string(&csv_path).unwrap()).unwrap();
+      assert_eq!(exported_csv, season("1"))
+    }
+
+    #[tokio::test]
+    async fn test_movie_export_error() {
+      let export_dir = tempdir().unwrap();
+      let export_path = export_dir.path().join("missing").join("movie.json");
+
+      let provider = InMemoryProvider { movie: Some(MovieMetadata::Single(MovieDefinition::new("The Big Lebowski".to_owned(), "659".to_owned()))), ..Default::default() };
+      let result = perform_movie(export_args(&export_path), &provider, URL).await;
+
+      assert!(matches!(result, Err(RenamerError::CouldNotExportMovieMetadata(..))))
+    }
+
+    #[tokio::test]
+    async fn test_series_export_from_html_file() {
+      let export_dir = tempdir().unwrap();
+      let export_path = export_dir.path().join("series.json");
+
+      let html_file = "tests/fixtures/html/x-men-evolution-season-1.html";
+      perform_series(export_args(&export_path), &HtmlFileProvider, html_file).await.unwrap();
+
+      let exported: EpisodesDefinition = serde_json::from_str(&std::fs::read_to_string(&export_path).unwrap()).unwrap();
+      assert_eq!(exported.metadata.name, "X-Men: Evolution");
+      assert_eq!(exported.metadata.season_number.value(), 1);
+      assert_eq!(exported.episodes.len(), 5)
+    }
+
+    fn season(season_number: &str) -> EpisodesDefinition {
+      EpisodesDefinition {
+        metadata: SeriesMetaData {
+          name: "Thundercats".to_owned(),
+          tvdb_id: Some("70355".to_owned()),
+          tmdb_id: None,
+          imdb_id: None,
+          season_number: season_number.parse().unwrap(),
+          order: None,
+          language: None,
+          year: None
+        },
+        episodes: vec![
+          EpisodeDefinition { number: format!("S{season_number:0>2}E01").parse().unwrap(), name: "Exodus".to_owned(), absolute_number: None, air_date: None, runtime: None, overview: None }
+        ],
+        extras: vec![]
+      }
+    }
+
+    #[tokio::test]
+    async fn test_get_all_seasons() {
+      let provider = InMemoryProvider { seasons: vec![season("1"), season("2")], ..Default::default() };
+      let seasons = get_all_seasons(&provider, URL, None, |url, _| Ok(url.to_owned())).await.unwrap();
+
+      assert_eq!(seasons, vec![season("1"), season("2")])
+    }
+
+    #[test]
+    fn test_backup_path() {
+      assert_eq!(backup_path(Path::new("/exports/thundercats.json")), PathBuf::from("/exports/thundercats.json.bak"))
+    }
+
+    #[test]
+    fn test_season_export_path() {
+      assert_eq!(season_export_path(Path::new("/exports/thundercats.json"), SeasonNumber::new(1).unwrap()), PathBuf::from("/exports/thundercats-s01.json"));
+      assert_eq!(season_export_path(Path::new("thundercats"), SeasonNumber::new(12).unwrap()), PathBuf::from("thundercats-s12"))
+    }
 }
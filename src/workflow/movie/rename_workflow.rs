@@ -1,57 +1,38 @@
 use std::path::{Path, PathBuf};
-use crate::html_scraper::get_movie_definition;
-use crate::metadata_downloader::download_metadata;
+use crate::metadata_provider::MetadataProvider;
+use crate::nfo::{self, NfoFile};
 use crate::models::*;
 use crate::cli::*;
-use super::super::common;
+use super::super::{artwork, common};
 
-pub async fn perform(rename_args: RenameArgs) -> ROutput {
+pub async fn perform<P: MetadataProvider>(rename_args: RenameArgs, provider: &P, source: &str) -> ROutput {
   let processing_dir_path = Path::new(&rename_args.processing_dir);
   let processing_dir = ProcessingDir(processing_dir_path.to_path_buf());
   let session_number = SessionNumberDir::new(rename_args.session_number);
-  let metadata_input_type = rename_args.metadata_input_type;
 
-  let metadata_type = common::get_metadata_type(&metadata_input_type);
-
-  match metadata_type {
-    ConfigMetadataInputType::Url(url) =>
-      handle_url_metadata(&url, &processing_dir, &session_number, rename_args.verbose, rename_args.skip_files).await,
-    ConfigMetadataInputType::File(file) => {
-      let file_path = Path::new(&file);
-      handle_file_metadata(file_path, &processing_dir, &session_number, rename_args.verbose, rename_args.skip_files)
-    },
-    ConfigMetadataInputType::Invalid => Err(RenamerError::InvalidMetadataConfiguration(format!("{:?}", &metadata_input_type))),
-  }
-}
+  let movie_metadata = common::with_processing_dir(provider.movie(source).await, &processing_dir)?;
 
+  // Retrieved before renaming, so a provider without artwork fails before any files are changed
+  let movie_artwork = if rename_args.artwork { Some(provider.movie_artwork(source).await?) } else { None };
 
-async fn handle_url_metadata(url: &str, processing_dir: &ProcessingDir, session_number: &SessionNumberDir, verbose: bool, skip_files: bool) -> ROutput {
-  let page_content = download_metadata(url).await?;
-  let movie_definition = get_movie_definition(&page_content);
+  let output = program(&processing_dir, &session_number, &movie_metadata, rename_args.include_year, rename_args.nfo, rename_args.verbose, rename_args.skip_files)?;
 
-  let processing_dir_path = processing_dir.as_ref();
-  if !processing_dir_path.exists() {
-      Err(RenamerError::ProcessingDirectoryDoesNotExist(processing_dir_path.to_owned()))
-  } else {
-    program(processing_dir, session_number, &movie_definition, verbose, skip_files)
-  }
-}
+  // Providers only return single movies, so box sets have no artwork
+  match (output, movie_artwork, provider.http_client(), &movie_metadata) {
+    (Output::Success, Some(movie_artwork), Some(http), MovieMetadata::Single(movie_definition)) => {
+      let movie_directory = get_movie_directory(&processing_dir.movies_encodes_dir(), movie_definition, rename_args.include_year);
+      let artwork_files = artwork::movie_artwork_files(&movie_artwork, &movie_directory);
 
-fn handle_file_metadata(series_metadata_path: &Path, processing_dir: &ProcessingDir, session_number: &SessionNumberDir, verbose: bool, skip_files: bool) -> ROutput {
-  let processing_dir_path = processing_dir.as_ref();
-  match (series_metadata_path.exists(), processing_dir_path.exists()) {
-      (true, true) => {
-        let movie_definition: MovieDefinition = common::read_input_from_file(series_metadata_path)?;
-        program(processing_dir, session_number, &movie_definition, verbose, skip_files)
-      },
-      (false, false) => Err(RenamerError::ProcessingDirAndMetadaPathDoesNotExit(processing_dir_path.to_owned(), series_metadata_path.to_owned())),
-      (_, false) => Err(RenamerError::ProcessingDirectoryDoesNotExist(processing_dir_path.to_owned())),
-      (false, _) => Err(RenamerError::MetadataDirectoryDoesNotExist(series_metadata_path.to_owned())),
+      // Failed images are shown as warnings, as the files have already been renamed
+      artwork::download_artwork(http, &artwork_files).await;
+      Ok(Output::Success)
+    },
+    (output, _, _, _) => Ok(output)
   }
 }
 
 
-fn program(processing_dir: &ProcessingDir, session_number: &SessionNumberDir, movie_definition: &MovieDefinition, verbose: bool, skip_files: bool) -> ROutput {
+fn program(processing_dir: &ProcessingDir, session_number: &SessionNumberDir, movie_metadata: &MovieMetadata, include_year: bool, write_nfo: bool, verbose: bool, skip_files: bool) -> ROutput {
   let rips_directory = processing_dir.rips_session_number(session_number);
   let renames_directory = processing_dir.rips_session_renames_dir(session_number);
   let encodes_directory = processing_dir.movies_encodes_dir();
@@ -60,37 +41,46 @@ fn program(processing_dir: &ProcessingDir, session_number: &SessionNumberDir, mo
 
   let ripped_filenames = common::get_ripped_filenames(&rips_directory);
 
-  // Skip files.
-  // Only create encode file and output directory
-  if skip_files {
-    let encoded_movie_directory = get_movie_directory(&encodes_directory, movie_definition);
-    let encoded_movie_directory_path = encoded_movie_directory.as_path();
+  let encoded_movie_directories: Vec<PathBuf> =
+    movie_metadata
+      .movies()
+      .into_iter()
+      .map(|movie_definition| get_movie_directory(&encodes_directory, movie_definition, include_year))
+      .collect();
 
-    if encoded_movie_directory_path.exists() {
-      return Err(RenamerError::MovieDirectoryAlreadyExists(encoded_movie_directory))
-    }
+  if let Some(existing_directory) = encoded_movie_directories.iter().find(|d| d.exists()) {
+    return Err(RenamerError::MovieDirectoryAlreadyExists(existing_directory.to_owned()))
+  }
+
+  let nfo_files =
+    if write_nfo {
+      get_nfo_files(&movie_metadata.movies(), &encoded_movie_directories)
+    } else {
+      vec![]
+    };
 
-    common::create_all_directories(encoded_movie_directory_path)
-      .and(common::write_encodes_file(&renames_directory, encoded_movie_directory_path))
+  // Skip files.
+  // Only create encode file and output directories
+  if skip_files {
+    create_movie_directories(&encoded_movie_directories)
+      .and(nfo::write_nfo_files(&nfo_files))
+      .and(common::write_encodes_file(&renames_directory, &encoded_movie_directories))
       .map(|_| Output::Success)
   } else if ripped_filenames.is_empty() {
     Err(RenamerError::NoMovieDefinitionFound)
   } else {
-    let encoded_movie_directory = get_movie_directory(&encodes_directory, movie_definition);
-    let encoded_movie_directory_path = encoded_movie_directory.as_path();
-
-    if encoded_movie_directory_path.exists() {
-      return Err(RenamerError::MovieDirectoryAlreadyExists(encoded_movie_directory))
-    }
-
-    let files_to_rename = get_files_to_rename(&ripped_filenames, movie_definition, &renames_directory);
+    let files_to_rename = match movie_metadata {
+      MovieMetadata::Single(movie_definition) => get_files_to_rename(&ripped_filenames, movie_definition, include_year, &renames_directory),
+      MovieMetadata::BoxSet(box_set) => get_box_set_files_to_rename(&ripped_filenames, box_set, include_year, &renames_directory)?,
+    };
 
     if !files_to_rename.is_empty() {
-      match common::confirm_changes(&files_to_rename, encoded_movie_directory_path) {
+      match common::confirm_changes(&files_to_rename, &encoded_movie_directories) {
         RenamesResult::Correct => {
-          common::perform_rename(&files_to_rename);
-          common::create_all_directories(encoded_movie_directory_path)
-            .and(common::write_encodes_file(&renames_directory, encoded_movie_directory_path))
+          common::perform_rename(&files_to_rename)
+            .and_then(|_| create_movie_directories(&encoded_movie_directories))
+            .and_then(|_| nfo::write_nfo_files(&nfo_files))
+            .and_then(|_| common::write_encodes_file(&renames_directory, &encoded_movie_directories))
             .map(|_| Output::Success)
         },
         RenamesResult::Wrong => Ok(Output::UserCanceled)
@@ -102,32 +92,158 @@ fn program(processing_dir: &ProcessingDir, session_number: &SessionNumberDir, mo
 }
 
 
-fn get_files_to_rename(ripped_movie_names: &[FileNameAndExt], movie_definition: &MovieDefinition, renames_dir: &RipsSessionRenamesDir) -> Vec<Rename> {
-  let renames_dir_path = renames_dir.as_ref();
+fn create_movie_directories(encoded_movie_directories: &[PathBuf]) -> R {
+  encoded_movie_directories
+    .iter()
+    .try_for_each(|d| common::create_all_directories(d))
+}
+
+
+fn get_nfo_files(movies: &[&MovieDefinition], encoded_movie_directories: &[PathBuf]) -> Vec<NfoFile> {
+  movies
+    .iter()
+    .zip(encoded_movie_directories)
+    .map(|(movie_definition, directory)| NfoFile::new(directory.join(nfo::MOVIE_NFO), nfo::movie_nfo(movie_definition)))
+    .collect()
+}
+
 
+fn get_files_to_rename(ripped_movie_names: &[FileNameAndExt], movie_definition: &MovieDefinition, include_year: bool, renames_dir: &RipsSessionRenamesDir) -> Vec<Rename> {
   ripped_movie_names
     .iter()
-    .map(|fne|{
-      let movie_name = movie_definition.name();
-      let tvdb_id = movie_definition.tvdb_id();
-      let ext = &fne.ext;
-      let file_name_with_ext = format!("{movie_name} - {{tvdb-{tvdb_id}}} [tvdbid-{tvdb_id}].{ext}");
-      let output_file_path = renames_dir_path.join(file_name_with_ext).to_path_buf();
-      let path_to_output_file = output_file_path.to_path_buf();
-      Rename::new(fne.clone().path, path_to_output_file)
-    })
+    .map(|fne| get_movie_rename(fne, movie_definition, include_year, renames_dir))
     .collect()
 }
 
 
-fn get_movie_folder_structure(movie_definition: &MovieDefinition) -> String {
-  let movie_name = movie_definition.name();
-  let tvdb_id = movie_definition.tvdb_id();
-  format!("{movie_name} - {{tvdb-{tvdb_id}}} [tvdbid-{tvdb_id}]")
+// Each box set movie must match exactly one ripped file, every ripped file must belong to a movie,
+// and no two movies can be renamed to the same file
+fn get_box_set_files_to_rename(ripped_movie_names: &[FileNameAndExt], box_set: &MovieBoxSetDefinition, include_year: bool, renames_dir: &RipsSessionRenamesDir) -> Result<Vec<Rename>, RenamerError> {
+  // Movies on the same disc are told apart by their titles
+  let duplicate_disc =
+    box_set
+      .movies
+      .iter()
+      .enumerate()
+      .find(|(index, movie)| {
+        box_set.movies[..*index]
+          .iter()
+          .any(|other| other.disc == movie.disc && (other.title.is_none() || movie.title.is_none() || other.title == movie.title))
+      });
+
+  if let Some((_, movie)) = duplicate_disc {
+    return Err(RenamerError::DuplicateBoxSetDisc(movie.disc.clone()))
+  }
+
+  if let Some(unmatched) = ripped_movie_names.iter().find(|fne| !box_set.movies.iter().any(|m| m.matches(fne))) {
+    return Err(RenamerError::NoMovieDefinitionForRippedFile(unmatched.path.clone()))
+  }
+
+  box_set
+    .movies
+    .iter()
+    .map(|box_set_movie| {
+      let matching_files: Vec<_> =
+        ripped_movie_names
+          .iter()
+          .filter(|fne| box_set_movie.matches(fne))
+          .collect();
+
+      match matching_files.as_slice() {
+        [] => Err(RenamerError::NoRippedFileForMovie(box_set_movie.movie.name().to_owned(), box_set_movie.disc.clone())),
+        [fne] => Ok(get_movie_rename(fne, &box_set_movie.movie, include_year, renames_dir)),
+        _ => Err(RenamerError::MultipleRippedFilesForMovie(box_set_movie.movie.name().to_owned(), matching_files.len())),
+      }
+    })
+    .collect::<Result<Vec<_>, _>>()
+    .and_then(|renames| common::check_duplicate_targets(&renames).map(|_| renames))
+}
+
+
+fn get_movie_rename(fne: &FileNameAndExt, movie_definition: &MovieDefinition, include_year: bool, renames_dir: &RipsSessionRenamesDir) -> Rename {
+  let renames_dir_path = renames_dir.as_ref();
+  let movie_folder_structure = get_movie_folder_structure(movie_definition, include_year);
+  let ext = &fne.ext;
+  let file_name_with_ext = format!("{movie_folder_structure}.{ext}");
+  let output_file_path = renames_dir_path.join(file_name_with_ext).to_path_buf();
+  let path_to_output_file = output_file_path.to_path_buf();
+  let details = fne.title_info.iter().map(|title_info| title_info.summary()).collect();
+  Rename::new(fne.clone().path, path_to_output_file).with_details(details, vec![])
 }
 
 
-fn get_movie_directory(encodes_dir: &EncodesDir, movie_definition: &MovieDefinition) -> PathBuf {
-  let movie_folder_structure = get_movie_folder_structure(movie_definition);
+fn get_movie_folder_structure(movie_definition: &MovieDefinition, include_year: bool) -> String {
+  let movie_name = name_with_year(movie_definition.name(), movie_definition.year().filter(|_| include_year));
+  match movie_definition.id_tags() {
+    Some(id_tags) => format!("{movie_name} - {id_tags}"),
+    None => movie_name
+  }
+}
+
+
+fn get_movie_directory(encodes_dir: &EncodesDir, movie_definition: &MovieDefinition, include_year: bool) -> PathBuf {
+  let movie_folder_structure = get_movie_folder_structure(movie_definition, include_year);
   encodes_dir.join(movie_folder_structure)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_movie_folder_structure() {
+      let movie = MovieDefinition::from_tmdb("The Matrix".to_owned(), "603".to_owned());
+
+      assert_eq!(get_movie_folder_structure(&movie, true), "The Matrix - {tmdb-603} [tmdbid-603]");
+
+      let movie = movie.with_year(Some(1999));
+      assert_eq!(get_movie_folder_structure(&movie, false), "The Matrix - {tmdb-603} [tmdbid-603]");
+      assert_eq!(get_movie_folder_structure(&movie, true), "The Matrix (1999) - {tmdb-603} [tmdbid-603]");
+
+      // Some names already end with the year
+      let movie = MovieDefinition::new("Dune (2021)".to_owned(), "13184".to_owned()).with_year(Some(2021));
+      assert_eq!(get_movie_folder_structure(&movie, true), "Dune (2021) - {tvdb-13184} [tvdbid-13184]")
+    }
+
+    #[test]
+    fn test_box_set_movie_without_ripped_file() {
+      let renames_dir = RipsSessionRenamesDir(PathBuf::from("/pd/Rips/session1/renames"));
+      let path = Path::new("/pd/Rips/session1/disc1/title_t00.mkv");
+      let ripped_files = vec![FileNameAndExt::new(path, path.file_name().unwrap(), path.extension().unwrap())];
+
+      let box_set =
+        MovieBoxSetDefinition {
+          movies: vec![
+            BoxSetMovieDefinition { disc: "disc1".to_owned(), title: None, movie: MovieDefinition::new("Back to the Future".to_owned(), "296".to_owned()) },
+            BoxSetMovieDefinition { disc: "disc2".to_owned(), title: None, movie: MovieDefinition::new("Back to the Future Part II".to_owned(), "297".to_owned()) },
+          ]
+        };
+
+      let result = get_box_set_files_to_rename(&ripped_files, &box_set, false, &renames_dir);
+      assert!(matches!(result, Err(RenamerError::NoRippedFileForMovie(movie, disc)) if movie == "Back to the Future Part II" && disc == "disc2"))
+    }
+
+    #[test]
+    fn test_box_set_duplicates() {
+      let renames_dir = RipsSessionRenamesDir(PathBuf::from("/pd/Rips/session1/renames"));
+      let ripped_files: Vec<_> =
+        ["/pd/Rips/session1/disc1/title_t00.mkv", "/pd/Rips/session1/disc2/title_t00.mkv"]
+          .iter()
+          .map(Path::new)
+          .map(|path| FileNameAndExt::new(path, path.file_name().unwrap(), path.extension().unwrap()))
+          .collect();
+
+      let movie = |disc: &str, title: Option<&str>, name: &str| {
+        BoxSetMovieDefinition { disc: disc.to_owned(), title: title.map(|t| t.to_owned()), movie: MovieDefinition::new(name.to_owned(), "296".to_owned()) }
+      };
+
+      let same_disc = MovieBoxSetDefinition { movies: vec![movie("disc1", None, "Back to the Future"), movie("disc1", Some("title_t01.mkv"), "Back to the Future Part II")] };
+      let result = get_box_set_files_to_rename(&ripped_files, &same_disc, false, &renames_dir);
+      assert!(matches!(result, Err(RenamerError::DuplicateBoxSetDisc(disc)) if disc == "disc1"));
+
+      let same_target = MovieBoxSetDefinition { movies: vec![movie("disc1", None, "Back to the Future"), movie("disc2", None, "Back to the Future")] };
+      let result = get_box_set_files_to_rename(&ripped_files, &same_target, false, &renames_dir);
+      assert!(matches!(result, Err(RenamerError::DuplicateRenameTarget(path)) if path == Path::new("/pd/Rips/session1/renames/Back to the Future - {tvdb-296} [tvdbid-296].mkv")))
+    }
+}
@@ -0,0 +1,145 @@
+use std::io::BufRead;
+use console::Style;
+use crate::cli::{ProviderArgs, SearchArgs};
+use crate::metadata_provider::{ordered_url, rank_results, MediaKind, MetadataProvider, Provider, SearchQuery, SearchResult};
+use crate::models::*;
+
+/// Searches the provider for the name and returns the provider along with the source of the chosen result.
+/// Returns None if the user does not choose a result.
+pub async fn perform(name: &str, kind: MediaKind, search_args: &SearchArgs, provider_args: &ProviderArgs) -> Result<Option<(Provider, String)>, RenamerError> {
+  let (provider, provider_type) = Provider::for_search(provider_args)?;
+
+  let query =
+    SearchQuery {
+      kind,
+      name: name.to_owned(),
+      year: search_args.year,
+      season: search_args.season.unwrap_or(1),
+    };
+
+  let results = rank_results(provider.search(&query).await?, &query);
+
+  if results.is_empty() {
+    return Err(RenamerError::NoSearchResults(name.to_owned()))
+  }
+
+  let chosen =
+    match search_args.pick {
+      Some(pick) => Some(pick_result(&results, pick)?),
+      None => choose_result(&results),
+    };
+
+  match chosen {
+    Some(result) => {
+      let source = ordered_url(&result.source, &provider_type, provider_args.order)?;
+      Ok(Some((provider, source)))
+    },
+    None => Ok(None)
+  }
+}
+
+fn pick_result(results: &[SearchResult], pick: u16) -> Result<&SearchResult, RenamerError> {
+  results
+    .get(usize::from(pick) - 1)
+    .ok_or(RenamerError::InvalidSearchPick(pick, results.len()))
+}
+
+fn choose_result(results: &[SearchResult]) -> Option<&SearchResult> {
+  println!("The following matches were found:");
+  let yellow = Style::new().yellow();
+
+  for (index, result) in results.iter().enumerate() {
+    println!("{}. {} [id: {}]", index + 1, yellow.apply_to(display_name(result)), result.id)
+  }
+  println!();
+
+  println!("Choose a number to continue or any other key to abort");
+
+  let mut user_response = String::new();
+  let stdin = std::io::stdin();
+  let mut handle = stdin.lock();
+  handle.read_line(&mut user_response).expect("Could not read from stdin"); // Unexpected, so throw
+
+  selected_result(results, &user_response)
+}
+
+fn selected_result<'a>(results: &'a [SearchResult], user_response: &str) -> Option<&'a SearchResult> {
+  user_response
+    .trim()
+    .parse::<usize>()
+    .ok()
+    .and_then(|n| n.checked_sub(1))
+    .and_then(|index| results.get(index))
+}
+
+fn display_name(result: &SearchResult) -> String {
+  match &result.year {
+    Some(year) => format!("{} ({year})", result.name),
+    None => result.name.clone()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use mockito::{Matcher, Server};
+    use crate::cli::{MkvCommands, MkvInputType, MkvRenamerArgs};
+    use pretty_assertions::assert_eq;
+
+    fn results() -> Vec<SearchResult> {
+      vec![
+        SearchResult { id: "74205".to_owned(), name: "Band of Brothers".to_owned(), year: Some("2001".to_owned()), source: "https://thetvdb.com/series/band-of-brothers/seasons/official/1".to_owned() },
+        SearchResult { id: "81232".to_owned(), name: "Band of Brothers: Behind the Scenes".to_owned(), year: None, source: "https://thetvdb.com/series/band-of-brothers-behind-the-scenes/seasons/official/1".to_owned() },
+      ]
+    }
+
+    #[test]
+    fn test_selected_result() {
+      let results = results();
+
+      assert_eq!(selected_result(&results, "2\n").map(|r| r.id.as_str()), Some("81232"));
+      assert_eq!(selected_result(&results, "0\n"), None);
+      assert_eq!(selected_result(&results, "3\n"), None);
+      assert_eq!(selected_result(&results, "n\n"), None)
+    }
+
+    #[test]
+    fn test_pick_result() {
+      let results = results();
+
+      assert_eq!(pick_result(&results, 1).unwrap().id, "74205");
+      assert!(matches!(pick_result(&results, 3), Err(RenamerError::InvalidSearchPick(3, 2))))
+    }
+
+    #[test]
+    fn test_display_name() {
+      let results = results();
+
+      assert_eq!(display_name(&results[0]), "Band of Brothers (2001)");
+      assert_eq!(display_name(&results[1]), "Band of Brothers: Behind the Scenes")
+    }
+
+    #[tokio::test]
+    async fn test_search_with_pick() {
+      let mut server = Server::new_async().await;
+
+      let _search =
+        server
+          .mock("GET", "/search/tv")
+          .match_query(Matcher::Any)
+          .with_body(r#"{ "results": [ { "id": 4613, "name": "Band of Brothers", "first_air_date": "2001-09-09" } ] }"#)
+          .create_async()
+          .await;
+
+      let args = MkvRenamerArgs::parse_from(["mkv-renamer", "series", "export", "--search", "Band of Brothers", "--season", "1", "--pick", "1", "--provider", "tmdb", "--tmdb-api-key", "key", "--tmdb-api-url", &server.url(), "--no-cache", "-e", "series.json"]);
+
+      let export_args = match args.commands {
+        MkvInputType::Series(MkvCommands::Export(export_args)) => export_args,
+        _ => unreachable!()
+      };
+
+      let (_, source) = perform("Band of Brothers", MediaKind::Series, &export_args.search, &export_args.provider).await.unwrap().unwrap();
+      assert_eq!(source, "https://www.themoviedb.org/tv/4613/season/1")
+    }
+}
@@ -1,2 +1,3 @@
 pub mod series_workflow;
 mod rename_workflow;
+mod extras;
@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use crate::models::*;
+
+const EXTRAS_DISC_SUFFIX: &str = "-extras";
+const ENCODED_EXTRAS_DIR: &str = "Featurettes";
+
+/// Ripped files split into episodes and extras matched to their definitions
+pub struct SessionFiles<'a> {
+  pub episodes: Vec<FileNameAndExt>,
+  pub extras: Vec<(FileNameAndExt, &'a ExtraDefinition)>,
+}
+
+/// Explicitly mapped extras (by disc or title) are matched first, and each must match a ripped file.
+/// Any remaining files in `discN-extras` directories are then matched positionally to the extras that have no mapping.
+/// Fails if two matched extras would have the same file name, as season and series extras can share a directory.
+pub fn split_extras(ripped_filenames: Vec<FileNameAndExt>, extras_definitions: &[ExtraDefinition]) -> Result<SessionFiles<'_>, RenamerError> {
+  let mut remaining: Vec<Option<FileNameAndExt>> = ripped_filenames.into_iter().map(Some).collect();
+  let mut extras = vec![];
+
+  for extra in extras_definitions.iter().filter(|e| e.is_mapped()) {
+    let matched =
+      remaining
+        .iter_mut()
+        .find(|fne| fne.as_ref().map(|f| extra.matches(f)).unwrap_or(false))
+        .and_then(|fne| fne.take());
+
+    match matched {
+      Some(fne) => extras.push((fne, extra)),
+      None => return Err(RenamerError::NoRippedFileForExtra(extra.name.clone()))
+    }
+  }
+
+  let (extras_files, episodes): (Vec<_>, Vec<_>) =
+    remaining
+      .into_iter()
+      .flatten()
+      .partition(is_on_extras_disc);
+
+  let unmapped_extras: Vec<_> = extras_definitions.iter().filter(|e| !e.is_mapped()).collect();
+
+  if extras_files.len() > unmapped_extras.len() {
+    return Err(RenamerError::NotEnoughMetadataForExtras(unmapped_extras.len(), extras_files.len()))
+  }
+
+  extras.extend(extras_files.into_iter().zip(unmapped_extras));
+
+  let mut file_names = HashSet::new();
+  if let Some((_, duplicate)) = extras.iter().find(|(fne, extra)| !file_names.insert(extra_file_name(extra, fne))) {
+    return Err(RenamerError::DuplicateExtraName(duplicate.name.clone()))
+  }
+
+  Ok(
+    SessionFiles {
+      episodes,
+      extras
+    }
+  )
+}
+
+pub fn get_extras_to_rename(extras: &[(FileNameAndExt, &ExtraDefinition)], renames_dir: &RipsSessionRenamesDir) -> Vec<Rename> {
+  extras
+    .iter()
+    .map(|(fne, extra)| {
+      let extras_dir = match extra.level {
+        ExtrasLevel::Season => renames_dir.season_extras_dir(),
+        ExtrasLevel::Series => renames_dir.series_extras_dir(),
+      };
+
+      Rename::new(fne.clone().path, extras_dir.join(extra_file_name(extra, fne)))
+    })
+    .collect()
+}
+
+/// Season extras are stored within the season directory and series extras alongside the seasons.
+/// Without season directories, both are stored in the same directory.
+pub fn get_extras_directories<I: IntoIterator<Item = ExtrasLevel>>(encoded_series_directory: &Path, encoded_season_directory: &Path, levels: I) -> Vec<PathBuf> {
+  let levels: Vec<_> = levels.into_iter().collect();
+  let mut directories = vec![];
+
+  if levels.contains(&ExtrasLevel::Season) {
+    directories.push(encoded_season_directory.join(ENCODED_EXTRAS_DIR))
+  }
+
+  if levels.contains(&ExtrasLevel::Series) {
+    directories.push(encoded_series_directory.join(ENCODED_EXTRAS_DIR))
+  }
+
+  directories.dedup();
+  directories
+}
+
+/// Where each extra will be encoded to, alongside the directories from `get_extras_directories`
+pub fn get_encoded_extras_files(extras: &[(FileNameAndExt, &ExtraDefinition)], encoded_series_directory: &Path, encoded_season_directory: &Path) -> Vec<PathBuf> {
+  extras
+    .iter()
+    .map(|(fne, extra)| {
+      let encoded_directory = match extra.level {
+        ExtrasLevel::Season => encoded_season_directory,
+        ExtrasLevel::Series => encoded_series_directory,
+      };
+
+      encoded_directory.join(ENCODED_EXTRAS_DIR).join(extra_file_name(extra, fne))
+    })
+    .collect()
+}
+
+fn extra_file_name(extra: &ExtraDefinition, fne: &FileNameAndExt) -> String {
+  format!("{}.{}", file_name_safe(&extra.name), fne.ext)
+}
+
+fn is_on_extras_disc(fne: &FileNameAndExt) -> bool {
+  fne
+    .path
+    .parent()
+    .and_then(|p| p.file_name())
+    .map(|d| d.to_string_lossy().ends_with(EXTRAS_DISC_SUFFIX))
+    .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn ripped_file(path: &str) -> FileNameAndExt {
+      let path = Path::new(path);
+      FileNameAndExt::new(path, path.file_name().unwrap(), path.extension().unwrap())
+    }
+
+    fn extra(name: &str, disc: Option<&str>, title: Option<&str>) -> ExtraDefinition {
+      ExtraDefinition { name: name.to_owned(), level: ExtrasLevel::Season, disc: disc.map(str::to_owned), title: title.map(str::to_owned) }
+    }
+
+    #[test]
+    fn test_split_extras() {
+      let ripped_files = vec![
+        ripped_file("/pd/Rips/session1/disc1/title_t00.mkv"),
+        ripped_file("/pd/Rips/session1/disc1/title_t01.mkv"),
+        ripped_file("/pd/Rips/session1/disc2-extras/title_t00.mkv"),
+      ];
+      let extras_definitions = vec![extra("Behind the Scenes", None, None), extra("Creating the Cats", Some("disc1"), Some("title_t01.mkv"))];
+
+      let session_files = split_extras(ripped_files, &extras_definitions).unwrap();
+
+      let episodes = session_files.episodes.iter().map(|fne| fne.path.to_string_lossy().to_string()).collect::<Vec<_>>();
+      let extras = session_files.extras.iter().map(|(fne, extra)| (fne.path.to_string_lossy().to_string(), extra.name.as_str())).collect::<Vec<_>>();
+
+      assert_eq!(episodes, vec!["/pd/Rips/session1/disc1/title_t00.mkv"]);
+      assert_eq!(
+        extras,
+        vec![
+          ("/pd/Rips/session1/disc1/title_t01.mkv".to_owned(), "Creating the Cats"),
+          ("/pd/Rips/session1/disc2-extras/title_t00.mkv".to_owned(), "Behind the Scenes"),
+        ]
+      )
+    }
+
+    #[test]
+    fn test_mapped_extra_without_ripped_file() {
+      let ripped_files = vec![ripped_file("/pd/Rips/session1/disc1/title_t00.mkv")];
+      let extras_definitions = vec![extra("Creating the Cats", Some("disc4"), Some("DVD_TS_03.mkv"))];
+
+      let result = split_extras(ripped_files, &extras_definitions);
+      assert!(matches!(result, Err(RenamerError::NoRippedFileForExtra(name)) if name == "Creating the Cats"))
+    }
+
+    #[test]
+    fn test_duplicate_extra_names() {
+      let ripped_files = vec![ripped_file("/pd/Rips/session1/disc1/title_t01.mkv"), ripped_file("/pd/Rips/session1/disc2-extras/title_t00.mkv")];
+      let extras_definitions = vec![extra("Behind the Scenes", None, None), extra("Behind the Scenes", Some("disc1"), Some("title_t01.mkv"))];
+
+      let result = split_extras(ripped_files, &extras_definitions);
+      assert!(matches!(result, Err(RenamerError::DuplicateExtraName(name)) if name == "Behind the Scenes"))
+    }
+
+    #[test]
+    fn test_extra_names_stay_in_extras_directory() {
+      let extra = extra("../Behind the Scenes/Part 1", None, None);
+      let extras = vec![(ripped_file("/pd/Rips/session1/disc2-extras/title_t00.mkv"), &extra)];
+      let renames = get_extras_to_rename(&extras, &RipsSessionRenamesDir(PathBuf::from("/pd/Rips/session1/renames")));
+
+      assert_eq!(renames[0].to_file_name, RipsSessionRenamesDir(PathBuf::from("/pd/Rips/session1/renames")).season_extras_dir().join("..-Behind the Scenes-Part 1.mkv"))
+    }
+}
@@ -1,104 +1,144 @@
 use std::path::{Path, PathBuf};
-use crate::html_scraper::get_series_metadata;
-use crate::metadata_downloader::download_metadata;
+use std::time::Duration;
+use crate::metadata_provider::MetadataProvider;
+use crate::mkv_duration;
+use crate::nfo::{self, NfoFile};
 use crate::models::*;
 use crate::cli::*;
-use super::super::common;
+use super::super::{artwork, common};
+use super::extras;
 
-pub async fn perform(rename_args: RenameArgs) -> ROutput {
-  let processing_dir_path = Path::new(&rename_args.processing_dir);
-  let processing_dir = ProcessingDir(processing_dir_path.to_path_buf());
-  let session_number = SessionNumberDir::new(rename_args.session_number);
-  let metadata_input_type = rename_args.metadata_input_type;
+// Ripped episodes differ from their listed runtimes through recaps, credits and rounding,
+// so only differences larger than this share of the runtime are reported
+const RUNTIME_TOLERANCE: f64 = 0.25;
 
-  let metadata_type = common::get_metadata_type(&metadata_input_type);
+// Overviews are shortened so each rename fits on a few lines when confirming
+const MAX_OVERVIEW_CHARS: usize = 100;
 
-  match metadata_type {
-    ConfigMetadataInputType::Url(url) =>
-      handle_url_metadata(&url, &processing_dir, &session_number, rename_args.verbose, rename_args.skip_files).await,
-    ConfigMetadataInputType::File(file) => {
-      let file_path = Path::new(&file);
-      handle_file_metadata(file_path, &processing_dir, &session_number, rename_args.verbose, rename_args.skip_files)
-    },
-    ConfigMetadataInputType::Invalid => Err(RenamerError::InvalidMetadataConfiguration(format!("{:?}", &metadata_input_type))),
+/// How renamed episodes and the directories they are encoded to are named
+struct SeriesNaming {
+  numbering: Numbering,
+  include_series_name: bool,
+  season_folder: bool,
+  include_year: bool,
+}
+
+impl SeriesNaming {
+  fn from_args(rename_args: &RenameArgs) -> Self {
+    Self {
+      numbering: Numbering { style: rename_args.numbering, width: rename_args.episode_width.map(usize::from) },
+      // Episodes named by date are matched with the series name by media servers
+      include_series_name: rename_args.include_series_name || rename_args.numbering == NumberingStyle::Date,
+      season_folder: !rename_args.no_season_folder,
+      include_year: rename_args.include_year,
+    }
   }
 }
 
+pub async fn perform<P: MetadataProvider>(rename_args: RenameArgs, provider: &P, source: &str) -> ROutput {
+  let processing_dir_path = Path::new(&rename_args.processing_dir);
+  let processing_dir = ProcessingDir(processing_dir_path.to_path_buf());
+  let session_number = SessionNumberDir::new(rename_args.session_number);
+
+  let naming = SeriesNaming::from_args(&rename_args);
 
-async fn handle_url_metadata(url: &str, processing_dir: &ProcessingDir, session_number: &SessionNumberDir, verbose: bool, skip_files: bool) -> ROutput {
-  let page_content = download_metadata(url).await?;
-  let episodes_definition = get_series_metadata(&page_content);
+  let episodes_definition = common::with_processing_dir(provider.series_season(source).await, &processing_dir)?;
 
-  let processing_dir_path = processing_dir.as_ref();
-  if !processing_dir_path.exists() {
-      Err(RenamerError::ProcessingDirectoryDoesNotExist(processing_dir_path.to_owned()))
-  } else {
-    program(processing_dir, session_number, &episodes_definition, verbose, skip_files)
-  }
-}
+  // Retrieved before renaming, so a provider without artwork fails before any files are changed
+  let series_artwork = if rename_args.artwork { Some(provider.series_artwork(source).await?) } else { None };
 
+  let output = program(&processing_dir, &session_number, &episodes_definition, &naming, rename_args.nfo, rename_args.verbose, rename_args.skip_files)?;
 
-fn handle_file_metadata(series_metadata_path: &Path, processing_dir: &ProcessingDir, session_number: &SessionNumberDir, verbose: bool, skip_files: bool) -> ROutput {
-  let processing_dir_path = processing_dir.as_ref();
-  match (series_metadata_path.exists(), processing_dir_path.exists()) {
-      (true, true) => {
-        let episodes_definition = common::read_input_from_file(series_metadata_path)?;
-        program(processing_dir, session_number, &episodes_definition, verbose, skip_files)
-      },
-      (false, false) => Err(RenamerError::ProcessingDirAndMetadaPathDoesNotExit(processing_dir_path.to_owned(), series_metadata_path.to_owned())),
-      (_, false) => Err(RenamerError::ProcessingDirectoryDoesNotExist(processing_dir_path.to_owned())),
-      (false, _) => Err(RenamerError::MetadataDirectoryDoesNotExist(series_metadata_path.to_owned())),
+  match (output, series_artwork, provider.http_client()) {
+    (Output::Success, Some(series_artwork), Some(http)) => {
+      let series_metadata = &episodes_definition.metadata;
+      let encoded_series_root = processing_dir.tv_encodes_dir().join(get_series_folder_name(series_metadata, naming.include_year));
+      let artwork_files = artwork::series_artwork_files(&series_artwork, &encoded_series_root, series_metadata.season_number);
+
+      // Failed images are shown as warnings, as the files have already been renamed
+      artwork::download_artwork(http, &artwork_files).await;
+      Ok(Output::Success)
+    },
+    (output, _, _) => Ok(output)
   }
 }
 
 
-fn program(processing_dir: &ProcessingDir, session_number: &SessionNumberDir, episodes_definition: &EpisodesDefinition, verbose: bool, skip_files: bool) -> ROutput {
+fn program(processing_dir: &ProcessingDir, session_number: &SessionNumberDir, episodes_definition: &EpisodesDefinition, naming: &SeriesNaming, write_nfo: bool, verbose: bool, skip_files: bool) -> ROutput {
   let metadata_episodes = &episodes_definition.episodes;
   let series_metadata = &episodes_definition.metadata;
 
   let rips_directory = processing_dir.rips_session_number(session_number);
   let renames_directory = processing_dir.rips_session_renames_dir(session_number);
   let encodes_directory = processing_dir.tv_encodes_dir();
+  let encoded_series_root = encodes_directory.join(get_series_folder_name(series_metadata, naming.include_year));
 
   common::dump_processing_info(processing_dir, session_number, verbose);
 
   // We want to skip files.
-  // Only create output directory and encodes file.
+  // Only create output directories and encodes file.
   if skip_files {
-      let encoded_series_directory = get_series_directory(&encodes_directory, series_metadata);
+      let encoded_series_directory = get_series_directory(&encodes_directory, series_metadata, naming);
       let encoded_series_directory_path = encoded_series_directory.as_path();
 
-      if encoded_series_directory_path.exists() {
-        return Err(RenamerError::SeriesDirectoryAlreadyExists(encoded_series_directory))
-      }
+      check_encoded_series_directory(encoded_series_directory_path, naming, &[])?;
+
+    let extras_directories = extras::get_extras_directories(&encoded_series_root, encoded_series_directory_path, episodes_definition.extras.iter().map(|e| e.level));
+    let encoded_directories = [vec![encoded_series_directory.clone()], extras_directories.clone()].concat();
+    let nfo_files = if write_nfo { get_nfo_files(series_metadata, &encoded_series_root, encoded_series_directory_path, &[]) } else { vec![] };
 
-    common::create_all_directories(encoded_series_directory_path)
-      .and(common::write_encodes_file(&renames_directory, encoded_series_directory_path))
+    create_series_directories(encoded_series_directory_path, &extras_directories, naming.season_folder)
+      .and(nfo::write_nfo_files(&nfo_files))
+      .and(common::write_encodes_file(&renames_directory, &encoded_directories))
       .map(|_| Output::Success)
   } else {
-    let mut ripped_episode_filenames = common::get_ripped_filenames(&rips_directory);
+    let mut ripped_filenames = common::get_ripped_filenames(&rips_directory);
     // Sort disk file names in ascending order
-    ripped_episode_filenames.sort_by(|fne1, fne2| fne1.partial_cmp(fne2).unwrap());
+    ripped_filenames.sort_by(|fne1, fne2| fne1.partial_cmp(fne2).unwrap());
+
+    // Extras are kept out of the positional episode matching
+    let session_files = extras::split_extras(ripped_filenames, &episodes_definition.extras)?;
+    let ripped_episode_filenames = session_files.episodes;
 
     // We have more ripped episodes than metadata episode names. Abort.
     if ripped_episode_filenames.len() > metadata_episodes.len() {
       Err(RenamerError::NotEnoughMetadataForEpisodes(metadata_episodes.len(), ripped_episode_filenames.len()))
     } else {
-      let encoded_series_directory = get_series_directory(&encodes_directory, series_metadata);
+      let encoded_series_directory = get_series_directory(&encodes_directory, series_metadata, naming);
       let encoded_series_directory_path = encoded_series_directory.as_path();
 
-      if encoded_series_directory_path.exists() {
-        return Err(RenamerError::SeriesDirectoryAlreadyExists(encoded_series_directory))
-      }
+      let extras_directories = extras::get_extras_directories(&encoded_series_root, encoded_series_directory_path, session_files.extras.iter().map(|(_, e)| e.level));
+      let encoded_directories = [vec![encoded_series_directory.clone()], extras_directories.clone()].concat();
+
+      let mut files_to_rename = get_files_to_rename(&ripped_episode_filenames, metadata_episodes, &series_metadata.name, naming, &renames_directory)?;
+
+      let encoded_files =
+        files_to_rename
+          .iter()
+          .filter_map(|rename| rename.to_file_name.file_name().map(|file_name| encoded_series_directory.join(file_name)))
+          .chain(extras::get_encoded_extras_files(&session_files.extras, &encoded_series_root, encoded_series_directory_path))
+          .collect::<Vec<_>>();
+
+      check_encoded_series_directory(encoded_series_directory_path, naming, &encoded_files)?;
+
+      // Episodes are renamed in the order they are listed, before any extras
+      let nfo_files =
+        if write_nfo {
+          let renamed_episodes = files_to_rename.iter().zip(metadata_episodes).collect::<Vec<_>>();
+          get_nfo_files(series_metadata, &encoded_series_root, encoded_series_directory_path, &renamed_episodes)
+        } else {
+          vec![]
+        };
 
-      let files_to_rename = get_files_to_rename(&ripped_episode_filenames, metadata_episodes, &renames_directory);
+      files_to_rename.extend(extras::get_extras_to_rename(&session_files.extras, &renames_directory));
 
       if !files_to_rename.is_empty() {
-        match common::confirm_changes(&files_to_rename, encoded_series_directory_path) {
+        match common::confirm_changes(&files_to_rename, &encoded_directories) {
           RenamesResult::Correct => {
-            common::perform_rename(&files_to_rename);
-            common::create_all_directories(encoded_series_directory_path)
-              .and(common::write_encodes_file(&renames_directory, encoded_series_directory_path))
+            common::perform_rename(&files_to_rename)
+              .and_then(|_| create_series_directories(encoded_series_directory_path, &extras_directories, naming.season_folder))
+              .and_then(|_| nfo::write_nfo_files(&nfo_files))
+              .and_then(|_| common::write_encodes_file(&renames_directory, &encoded_directories))
               .map(|_| Output::Success)
           },
           RenamesResult::Wrong => Ok(Output::UserCanceled)
@@ -111,7 +151,54 @@ fn program(processing_dir: &ProcessingDir, session_number: &SessionNumberDir, ep
 }
 
 
-fn get_files_to_rename(ripped_episode_filenames: &[FileNameAndExt], metadata_episodes: &[EpisodeDefinition], renames_dir: &RipsSessionRenamesDir) -> Vec<Rename> {
+// Each season has its own directory, which must not exist yet. Without season folders, every session and season
+// is encoded into the series directory, so only the files that will be encoded into it must not exist yet.
+fn check_encoded_series_directory(encoded_series_directory: &Path, naming: &SeriesNaming, encoded_files: &[PathBuf]) -> R {
+  if naming.season_folder {
+    match encoded_series_directory.exists() {
+      true => Err(RenamerError::SeriesDirectoryAlreadyExists(encoded_series_directory.to_owned())),
+      false => Ok(())
+    }
+  } else {
+    match encoded_files.iter().find(|f| f.exists()) {
+      Some(existing_file) => Err(RenamerError::EncodedFileAlreadyExists(existing_file.to_owned())),
+      None => Ok(())
+    }
+  }
+}
+
+
+// Series level extras directories are shared across seasons, so they may already exist.
+// Without season folders, so may the series directory.
+fn create_series_directories(encoded_series_directory: &Path, extras_directories: &[PathBuf], season_folder: bool) -> R {
+  if season_folder || !encoded_series_directory.exists() {
+    common::create_all_directories(encoded_series_directory)?;
+  }
+
+  extras_directories
+    .iter()
+    .filter(|d| !d.exists())
+    .try_for_each(|d| common::create_all_directories(d))
+}
+
+
+// The series NFO goes in the series directory, and each episode NFO next to where its file will be encoded
+fn get_nfo_files(series_metadata: &SeriesMetaData, encoded_series_root: &Path, encoded_series_directory: &Path, renamed_episodes: &[(&Rename, &EpisodeDefinition)]) -> Vec<NfoFile> {
+  let tvshow_nfo = NfoFile::new(encoded_series_root.join(nfo::TVSHOW_NFO), nfo::tvshow_nfo(series_metadata));
+
+  let episode_nfos =
+    renamed_episodes
+      .iter()
+      .map(|(rename, episode)| {
+        let file_name = Path::new(rename.to_file_name.file_name().unwrap_or_default()).with_extension(nfo::NFO_EXT);
+        NfoFile::new(encoded_series_directory.join(file_name), nfo::episode_nfo(series_metadata, episode))
+      });
+
+  std::iter::once(tvshow_nfo).chain(episode_nfos).collect()
+}
+
+
+fn get_files_to_rename(ripped_episode_filenames: &[FileNameAndExt], metadata_episodes: &[EpisodeDefinition], series_name: &str, naming: &SeriesNaming, renames_dir: &RipsSessionRenamesDir) -> Result<Vec<Rename>, RenamerError> {
   let renames_dir_path = renames_dir.as_ref();
 
   ripped_episode_filenames
@@ -119,25 +206,346 @@ fn get_files_to_rename(ripped_episode_filenames: &[FileNameAndExt], metadata_epi
     .enumerate()
     .map(|(i, fne)|{
       let episode = metadata_episodes.get(i).unwrap_or_else(|| panic!("could not read metadata_episodes index: {}", i));
-      let file_name_with_ext = format!("{} - {}.{}", episode.number, episode.name, fne.ext);
+      let number = naming.numbering.format(episode).map_err(|e| RenamerError::CouldNotNumberEpisode(episode.number.to_string(), e))?;
+      let file_name_with_ext =
+        if naming.include_series_name {
+          format!("{} - {} - {}.{}", series_name, number, file_name_safe(&episode.name), fne.ext)
+        } else {
+          format!("{} - {}.{}", number, file_name_safe(&episode.name), fne.ext)
+        };
 
       let output_file_path = renames_dir_path.join(file_name_with_ext).to_path_buf();
       let path_to_output_file = output_file_path.to_path_buf();
-      Rename::new(fne.clone().path, path_to_output_file)
+
+      // MakeMKV's listed duration is used when the file can't be read
+      let duration = mkv_duration::read_duration(&fne.path).or_else(|| fne.title_info.as_ref().and_then(|title_info| title_info.duration));
+
+      let warnings =
+        episode
+          .runtime
+          .zip(duration)
+          .and_then(|(runtime, duration)| runtime_warning(duration, runtime))
+          .into_iter()
+          .collect();
+
+      let details =
+        episode_details(episode)
+          .into_iter()
+          .chain(fne.title_info.as_ref().map(|title_info| title_info.summary()))
+          .collect();
+
+      Ok(Rename::new(fne.clone().path, path_to_output_file).with_details(details, warnings))
     })
     .collect()
 }
 
 
-fn get_series_folder_structure(series_metadata: &SeriesMetaData) -> String {
-  let series_name = series_metadata.name.clone();
-  let tvdb_id = series_metadata.tvdb_id.clone();
-  let season_number = series_metadata.season_number.clone();
-  format!("{series_name} {{tvdb-{tvdb_id}}} [tvdbid-{tvdb_id}]/Season {season_number:0>2}")
+fn episode_details(episode: &EpisodeDefinition) -> Vec<String> {
+  let aired_and_runtime =
+    [
+      episode.air_date.map(|air_date| format!("Aired: {air_date}")),
+      episode.runtime.map(|runtime| format!("Runtime: {runtime} min")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(", ");
+
+  let overview =
+    episode
+      .overview
+      .as_ref()
+      .map(|overview| {
+        if overview.chars().count() > MAX_OVERVIEW_CHARS {
+          format!("{}...", overview.chars().take(MAX_OVERVIEW_CHARS).collect::<String>().trim_end())
+        } else {
+          overview.clone()
+        }
+      });
+
+  Some(aired_and_runtime)
+    .filter(|line| !line.is_empty())
+    .into_iter()
+    .chain(overview)
+    .collect()
+}
+
+
+fn runtime_warning(duration: Duration, runtime: u32) -> Option<String> {
+  let ripped_minutes = duration.as_secs_f64() / 60.0;
+  let runtime_minutes = f64::from(runtime);
+
+  if (ripped_minutes - runtime_minutes).abs() > runtime_minutes * RUNTIME_TOLERANCE {
+    Some(format!("The ripped file is {} min, but the episode has a runtime of {runtime} min", ripped_minutes.round()))
+  } else {
+    None
+  }
+}
+
+
+fn get_series_folder_name(series_metadata: &SeriesMetaData, include_year: bool) -> String {
+  let series_name = name_with_year(&series_metadata.name, series_metadata.year.filter(|_| include_year));
+  match series_metadata.id_tags() {
+    Some(id_tags) => format!("{series_name} {id_tags}"),
+    None => series_name
+  }
+}
+
+
+fn get_series_folder_structure(series_metadata: &SeriesMetaData, naming: &SeriesNaming) -> String {
+  let series_folder_name = get_series_folder_name(series_metadata, naming.include_year);
+  let season_number = series_metadata.season_number.value();
+
+  if naming.season_folder {
+    format!("{series_folder_name}/Season {season_number:02}")
+  } else {
+    series_folder_name
+  }
 }
 
 
-fn get_series_directory(encodes_dir: &EncodesDir, series_metadata: &SeriesMetaData) -> PathBuf {
-  let series_folder_structure = get_series_folder_structure(series_metadata);
+fn get_series_directory(encodes_dir: &EncodesDir, series_metadata: &SeriesMetaData, naming: &SeriesNaming) -> PathBuf {
+  let series_folder_structure = get_series_folder_structure(series_metadata, naming);
   encodes_dir.join(series_folder_structure)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use tempfile::tempdir;
+    use crate::metadata_provider::InMemoryProvider;
+    use pretty_assertions::assert_eq;
+
+    fn rename_args(processing_dir: &Path) -> RenameArgs {
+      let args = MkvRenamerArgs::parse_from(["mkv-renamer", "series", "rename", "-p", &processing_dir.to_string_lossy(), "-s", "1", "-u", "url", "--skip-files"]);
+      match args.commands {
+        MkvInputType::Series(MkvCommands::Rename(rename_args)) => rename_args,
+        _ => unreachable!()
+      }
+    }
+
+    fn naming(style: NumberingStyle, width: Option<usize>, include_series_name: bool) -> SeriesNaming {
+      SeriesNaming { numbering: Numbering { style, width }, include_series_name, season_folder: true, include_year: false }
+    }
+
+    #[tokio::test]
+    async fn test_skip_files_with_provider_metadata() {
+      let processing_dir = tempdir().unwrap();
+      let renames_dir = processing_dir.path().join("Rips").join("session1").join("renames");
+      std::fs::create_dir_all(&renames_dir).unwrap();
+
+      let episodes_definition =
+        EpisodesDefinition {
+          metadata: SeriesMetaData {
+            name: "Game of Thrones".to_owned(),
+            tvdb_id: None,
+            tmdb_id: Some("1399".to_owned()),
+            imdb_id: None,
+            season_number: "2".parse().unwrap(),
+            order: None,
+            language: None,
+            year: None
+          },
+          episodes: vec![],
+          extras: vec![]
+        };
+
+      let provider = InMemoryProvider { series: Some(episodes_definition), ..Default::default() };
+      let result = perform(rename_args(processing_dir.path()), &provider, "url").await;
+
+      let season_dir = processing_dir.path().join("Encodes").join("tv").join("Game of Thrones {tmdb-1399} [tmdbid-1399]").join("Season 02");
+      let encodes_file = std::fs::read_to_string(renames_dir.join(common::ENCODES_FILE)).unwrap();
+
+      assert!(matches!(result, Ok(Output::Success)));
+      assert!(season_dir.exists());
+      assert_eq!(encodes_file, season_dir.to_string_lossy())
+    }
+
+    #[tokio::test]
+    async fn test_sessions_without_season_folders_share_series_directory() {
+      let processing_dir = tempdir().unwrap();
+      let metadata =
+        SeriesMetaData {
+          name: "One Piece".to_owned(),
+          tvdb_id: Some("81797".to_owned()),
+          tmdb_id: None,
+          imdb_id: None,
+          season_number: "1".parse().unwrap(),
+          order: None,
+          language: None,
+          year: None
+        };
+
+      let provider = InMemoryProvider { series: Some(EpisodesDefinition { metadata, episodes: vec![], extras: vec![] }), ..Default::default() };
+
+      for session in ["1", "2"] {
+        std::fs::create_dir_all(processing_dir.path().join("Rips").join(format!("session{session}")).join("renames")).unwrap();
+
+        let args = MkvRenamerArgs::parse_from(["mkv-renamer", "series", "rename", "-p", &processing_dir.path().to_string_lossy(), "-s", session, "-u", "url", "--skip-files", "--no-season-folder"]);
+        let rename_args = match args.commands { MkvInputType::Series(MkvCommands::Rename(rename_args)) => rename_args, _ => unreachable!() };
+
+        let result = perform(rename_args, &provider, "url").await;
+        assert!(matches!(result, Ok(Output::Success)), "session {session}")
+      }
+
+      assert!(processing_dir.path().join("Encodes").join("tv").join("One Piece {tvdb-81797} [tvdbid-81797]").exists())
+    }
+
+    #[test]
+    fn test_check_encoded_series_directory() {
+      let encodes_dir = tempdir().unwrap();
+      let series_dir = encodes_dir.path().join("One Piece {tvdb-81797} [tvdbid-81797]");
+      std::fs::create_dir_all(&series_dir).unwrap();
+      std::fs::write(series_dir.join("S01E01 - I'm Luffy!.mkv"), "").unwrap();
+
+      let without_season_folder = SeriesNaming { season_folder: false, ..naming(NumberingStyle::Upper, None, false) };
+      let new_files = vec![series_dir.join("S01E02 - Enter the Great Swordsman.mkv")];
+      let existing_files = vec![series_dir.join("S01E01 - I'm Luffy!.mkv")];
+
+      assert!(check_encoded_series_directory(&series_dir, &without_season_folder, &new_files).is_ok());
+      assert!(matches!(check_encoded_series_directory(&series_dir, &without_season_folder, &existing_files), Err(RenamerError::EncodedFileAlreadyExists(_))));
+      assert!(matches!(check_encoded_series_directory(&series_dir, &naming(NumberingStyle::Upper, None, false), &new_files), Err(RenamerError::SeriesDirectoryAlreadyExists(_))))
+    }
+
+    #[test]
+    fn test_files_to_rename_with_numbering() {
+      let renames_dir = RipsSessionRenamesDir(PathBuf::from("/pd/Rips/session1/renames"));
+      let ripped_files = vec![FileNameAndExt::new(Path::new("/pd/Rips/session1/disc1/title_t00.mkv"), std::ffi::OsStr::new("title_t00.mkv"), std::ffi::OsStr::new("mkv"))];
+      let episodes = vec![EpisodeDefinition { number: "S01E01".parse().unwrap(), name: "Exodus".to_owned(), absolute_number: None, air_date: None, runtime: None, overview: None }];
+
+      let renames = get_files_to_rename(&ripped_files, &episodes, "Thundercats", &naming(NumberingStyle::Cross, Some(3), false), &renames_dir).unwrap();
+      assert_eq!(renames[0].to_file_name, PathBuf::from("/pd/Rips/session1/renames/1x001 - Exodus.mkv"));
+
+      let result = get_files_to_rename(&ripped_files, &episodes, "Thundercats", &naming(NumberingStyle::Absolute, None, false), &renames_dir);
+      assert!(matches!(result, Err(RenamerError::CouldNotNumberEpisode(_, _))))
+    }
+
+    #[test]
+    fn test_files_to_rename_with_series_name() {
+      let renames_dir = RipsSessionRenamesDir(PathBuf::from("/pd/Rips/session1/renames"));
+      let ripped_files = vec![FileNameAndExt::new(Path::new("/pd/Rips/session1/disc1/title_t00.mkv"), std::ffi::OsStr::new("title_t00.mkv"), std::ffi::OsStr::new("mkv"))];
+      let episodes = vec![EpisodeDefinition { number: "S01E101".parse().unwrap(), name: "The Book of Omens".to_owned(), absolute_number: Some(101), air_date: None, runtime: None, overview: None }];
+
+      let renames = get_files_to_rename(&ripped_files, &episodes, "Thundercats", &naming(NumberingStyle::Absolute, None, true), &renames_dir).unwrap();
+      assert_eq!(renames[0].to_file_name, PathBuf::from("/pd/Rips/session1/renames/Thundercats - 101 - The Book of Omens.mkv"));
+
+      let renames = get_files_to_rename(&ripped_files, &episodes, "Thundercats", &naming(NumberingStyle::UpperAbsolute, None, false), &renames_dir).unwrap();
+      assert_eq!(renames[0].to_file_name, PathBuf::from("/pd/Rips/session1/renames/S01E101 - 101 - The Book of Omens.mkv"))
+    }
+
+    #[test]
+    fn test_files_to_rename_with_title_info() {
+      let renames_dir = RipsSessionRenamesDir(PathBuf::from("/pd/Rips/session1/renames"));
+      let title_info = TitleInfo { index: 1, duration: Some(Duration::from_secs(11 * 60)), chapters: Some(3), source_file_name: Some("00801.mpls".to_owned()), ..TitleInfo::default() };
+      let ripped_files = vec![FileNameAndExt::new(Path::new("/pd/Rips/session1/disc1/title_t01.mkv"), std::ffi::OsStr::new("title_t01.mkv"), std::ffi::OsStr::new("mkv")).with_title_info(Some(title_info))];
+      let episodes = vec![EpisodeDefinition { number: "S01E01".parse().unwrap(), name: "Exodus".to_owned(), absolute_number: None, air_date: None, runtime: Some(22), overview: None }];
+
+      let renames = get_files_to_rename(&ripped_files, &episodes, "Thundercats", &naming(NumberingStyle::Upper, None, false), &renames_dir).unwrap();
+      assert_eq!(renames[0].details, vec!["Runtime: 22 min".to_owned(), "Disc title 1 (00801.mpls): 0:11:00, 3 chapters".to_owned()]);
+      assert_eq!(renames[0].warnings, vec!["The ripped file is 11 min, but the episode has a runtime of 22 min".to_owned()])
+    }
+
+    #[test]
+    fn test_date_naming_includes_series_name() {
+      let args = MkvRenamerArgs::parse_from(["mkv-renamer", "series", "rename", "-p", "/pd", "-s", "1", "-u", "url", "--numbering", "date"]);
+      let rename_args = match args.commands { MkvInputType::Series(MkvCommands::Rename(rename_args)) => rename_args, _ => unreachable!() };
+
+      let renames_dir = RipsSessionRenamesDir(PathBuf::from("/pd/Rips/session1/renames"));
+      let ripped_files = vec![FileNameAndExt::new(Path::new("/pd/Rips/session1/disc1/title_t00.mkv"), std::ffi::OsStr::new("title_t00.mkv"), std::ffi::OsStr::new("mkv"))];
+      let episodes = vec![EpisodeDefinition { number: "S28E10".parse().unwrap(), name: "Christopher Walken".to_owned(), absolute_number: None, air_date: Some("2003-04-12".parse().unwrap()), runtime: None, overview: None }];
+
+      let renames = get_files_to_rename(&ripped_files, &episodes, "Saturday Night Live", &SeriesNaming::from_args(&rename_args), &renames_dir).unwrap();
+      assert_eq!(renames[0].to_file_name, PathBuf::from("/pd/Rips/session1/renames/Saturday Night Live - 2003-04-12 - Christopher Walken.mkv"))
+    }
+
+    #[test]
+    fn test_episode_details() {
+      let episode =
+        EpisodeDefinition {
+          number: "S01E01".parse().unwrap(),
+          name: "Exodus".to_owned(),
+          absolute_number: None,
+          air_date: Some("1985-01-23".parse().unwrap()),
+          runtime: Some(22),
+          overview: Some("The ThunderCats flee their dying planet, Thundera, and are pursued by the Mutants of Plun-Darr to Third Earth.".to_owned())
+        };
+
+      assert_eq!(
+        episode_details(&episode),
+        vec![
+          "Aired: 1985-01-23, Runtime: 22 min".to_owned(),
+          "The ThunderCats flee their dying planet, Thundera, and are pursued by the Mutants of Plun-Darr to Th...".to_owned()
+        ]
+      );
+
+      let episode = EpisodeDefinition { air_date: None, runtime: None, overview: None, ..episode };
+      assert!(episode_details(&episode).is_empty())
+    }
+
+    #[test]
+    fn test_runtime_warning() {
+      assert_eq!(runtime_warning(Duration::from_secs(21 * 60 + 30), 22), None);
+      assert_eq!(runtime_warning(Duration::from_secs(44 * 60), 22), Some("The ripped file is 44 min, but the episode has a runtime of 22 min".to_owned()))
+    }
+
+    #[test]
+    fn test_series_folder_structure() {
+      let metadata =
+        SeriesMetaData {
+          name: "Thundercats".to_owned(),
+          tvdb_id: Some("70355".to_owned()),
+          tmdb_id: None,
+          imdb_id: None,
+          season_number: "1".parse().unwrap(),
+          order: Some(EpisodeOrder::Absolute),
+          language: None,
+          year: None
+        };
+
+      let naming = |season_folder, include_year| SeriesNaming { numbering: Numbering::default(), include_series_name: false, season_folder, include_year };
+
+      assert_eq!(get_series_folder_structure(&metadata, &naming(true, false)), "Thundercats {tvdb-70355} [tvdbid-70355]/Season 01");
+      assert_eq!(get_series_folder_structure(&metadata, &naming(false, false)), "Thundercats {tvdb-70355} [tvdbid-70355]");
+      assert_eq!(get_series_folder_structure(&metadata, &naming(false, true)), "Thundercats {tvdb-70355} [tvdbid-70355]");
+
+      let metadata = SeriesMetaData { year: Some(1985), ..metadata };
+      assert_eq!(get_series_folder_structure(&metadata, &naming(true, true)), "Thundercats (1985) {tvdb-70355} [tvdbid-70355]/Season 01")
+    }
+
+    #[test]
+    fn test_nfo_files() {
+      let metadata =
+        SeriesMetaData {
+          name: "Thundercats".to_owned(),
+          tvdb_id: Some("70355".to_owned()),
+          tmdb_id: None,
+          imdb_id: None,
+          season_number: "1".parse().unwrap(),
+          order: None,
+          language: None,
+          year: None
+        };
+      let episode = EpisodeDefinition { number: "S01E01".parse().unwrap(), name: "Exodus".to_owned(), absolute_number: None, air_date: None, runtime: None, overview: None };
+      let rename = Rename::new(PathBuf::from("/rips/session1/disc1/title_t00.mkv"), PathBuf::from("/rips/session1/renames/S01E01 - Exodus.mkv"));
+
+      let series_root = Path::new("/encodes/tv/Thundercats {tvdb-70355} [tvdbid-70355]");
+      let season_directory = series_root.join("Season 01");
+
+      let paths =
+        get_nfo_files(&metadata, series_root, &season_directory, &[(&rename, &episode)])
+          .into_iter()
+          .map(|nfo_file| nfo_file.path)
+          .collect::<Vec<_>>();
+
+      assert_eq!(paths, vec![series_root.join("tvshow.nfo"), season_directory.join("S01E01 - Exodus.nfo")])
+    }
+
+    #[tokio::test]
+    async fn test_provider_error_is_returned() {
+      let provider = InMemoryProvider::default();
+      let result = perform(rename_args(Path::new("/does/not/exist")), &provider, "url").await;
+
+      assert!(matches!(result, Err(RenamerError::CouldNotAccessMetadataURL(_, _))))
+    }
+}
@@ -1,11 +1,36 @@
-use crate::{cli::MkvCommands, html_scraper};
-use crate::models::ROutput;
+use crate::cli::MkvCommands;
+use crate::metadata_provider::{MediaKind, Provider};
+use crate::models::{Output, ROutput};
 use super::rename_workflow;
-use super::super::export_workflow;
+use super::super::{export_workflow, search_workflow, validate_workflow};
 
 pub async fn perform(series_command: MkvCommands) -> ROutput {
   match series_command {
-    MkvCommands::Rename(rename_args) => rename_workflow::perform(rename_args).await,
-    MkvCommands::Export(export_args) => export_workflow::perform(export_args, html_scraper::get_series_metadata).await,
+    MkvCommands::Rename(rename_args) => {
+      let provider_and_source =
+        match &rename_args.metadata_input_type.search {
+          Some(name) => search_workflow::perform(name, MediaKind::Series, &rename_args.search, &rename_args.provider).await?,
+          None => Some(Provider::for_input(&rename_args.metadata_input_type, &rename_args.provider, rename_args.search.season, rename_args.format)?),
+        };
+
+      match provider_and_source {
+        Some((provider, source)) => rename_workflow::perform(rename_args, &provider, &source).await,
+        None => Ok(Output::UserCanceled)
+      }
+    },
+    MkvCommands::Export(export_args) => {
+      let provider_and_source =
+        match &export_args.export_input_type.search {
+          Some(name) => search_workflow::perform(name, MediaKind::Series, &export_args.search, &export_args.provider).await?,
+          None => Some(Provider::for_export(&export_args.export_input_type, &export_args.provider)?),
+        };
+
+      match provider_and_source {
+        Some((provider, source)) if export_args.all_seasons => export_workflow::perform_all_seasons(export_args, &provider, &source).await,
+        Some((provider, source)) => export_workflow::perform_series(export_args, &provider, &source).await,
+        None => Ok(Output::UserCanceled)
+      }
+    },
+    MkvCommands::Validate(validate_args) => validate_workflow::perform(validate_args, MediaKind::Series),
   }
 }
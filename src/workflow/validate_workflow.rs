@@ -0,0 +1,348 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use console::Style;
+use serde::de::DeserializeOwned;
+use crate::cli::ValidateArgs;
+use crate::metadata_provider::{read_input_from_file, MediaKind};
+use crate::models::*;
+
+// Characters that can't be used in file names on at least one of the common file systems
+const UNSAFE_NAME_CHARS: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// A problem found in a metadata file, along with the path of the field it was found in. Example: episodes.2.name
+#[derive(Debug, PartialEq)]
+struct Issue {
+  location: String,
+  message: String,
+}
+
+impl Issue {
+  fn new(location: &str, message: String) -> Self {
+    Self {
+      location: location.to_owned(),
+      message
+    }
+  }
+}
+
+/// Checks a metadata file for problems that would stop it from being renamed from, or give unexpected names
+pub fn perform(validate_args: ValidateArgs, kind: MediaKind) -> ROutput {
+  let path = validate_args.file_metadata.as_path();
+  let format = MetadataFormat::for_path(path, validate_args.format);
+
+  let issues =
+    match kind {
+      MediaKind::Series => {
+        let metadata: SeriesMetadataFile = read_input_from_file(path, Some(format))?;
+        let unknown_fields =
+          match &metadata {
+            SeriesMetadataFile::Season(_) => unknown_field_issues::<EpisodesDefinition>(path, format)?,
+            SeriesMetadataFile::AllSeasons(_) => unknown_field_issues::<SeasonsDefinition>(path, format)?,
+          };
+
+        series_issues(&metadata).into_iter().chain(unknown_fields).collect::<Vec<_>>()
+      },
+      MediaKind::Movie if format == MetadataFormat::Csv => {
+        return Err(RenamerError::MetadataFormatNotSupported(format.as_str().to_owned(), "movies".to_owned()))
+      },
+      MediaKind::Movie => {
+        let metadata: MovieMetadata = read_input_from_file(path, Some(format))?;
+        let unknown_fields =
+          match &metadata {
+            MovieMetadata::Single(_) => unknown_field_issues::<MovieDefinition>(path, format)?,
+            MovieMetadata::BoxSet(_) => unknown_field_issues::<MovieBoxSetDefinition>(path, format)?,
+          };
+
+        movie_issues(&metadata).into_iter().chain(unknown_fields).collect::<Vec<_>>()
+      },
+    };
+
+  if issues.is_empty() {
+    Ok(Output::Valid)
+  } else {
+    let yellow = Style::new().yellow();
+
+    for issue in &issues {
+      println!("{}: {}", yellow.apply_to(&issue.location), issue.message)
+    }
+    println!();
+
+    Err(RenamerError::InvalidMetadataFile(path.to_owned(), issues.len()))
+  }
+}
+
+// Fields are only reported for the metadata the file was decoded as
+fn unknown_field_issues<R: DeserializeOwned>(path: &Path, format: MetadataFormat) -> Result<Vec<Issue>, RenamerError> {
+  let decode_error = |e: String| RenamerError::CouldNotDecodeMetadataFile(path.to_owned(), format.as_str().to_owned(), e);
+
+  let content =
+    fs::read_to_string(path)
+      .map_err(|e| RenamerError::CouldNotAccessMetadataFile(path.to_string_lossy().to_string(), e.to_string()))?;
+
+  let unknown_fields = format.unknown_fields::<R>(&content).map_err(decode_error)?;
+
+  Ok(
+    unknown_fields
+      .iter()
+      .map(|field| Issue::new(field, "Unknown field. It will be ignored".to_owned()))
+      .collect()
+  )
+}
+
+fn series_issues(metadata: &SeriesMetadataFile) -> Vec<Issue> {
+  match metadata {
+    SeriesMetadataFile::Season(episodes_definition) => season_issues(episodes_definition, ""),
+    SeriesMetadataFile::AllSeasons(seasons_definition) => {
+      seasons_definition
+        .seasons
+        .iter()
+        .enumerate()
+        .flat_map(|(index, episodes_definition)| season_issues(episodes_definition, &format!("seasons.{index}.")))
+        .collect()
+    }
+  }
+}
+
+fn season_issues(episodes_definition: &EpisodesDefinition, prefix: &str) -> Vec<Issue> {
+  let metadata = &episodes_definition.metadata;
+  let mut issues = vec![];
+
+  issues.extend(name_issue(&metadata.name, &format!("{prefix}metadata.name")));
+  issues.extend(missing_id_issue(metadata.id_tags(), &format!("{prefix}metadata.tvdb_id")));
+  issues.extend(id_issue(metadata.tvdb_id.as_deref(), "TVDB", &format!("{prefix}metadata.tvdb_id")));
+  issues.extend(id_issue(metadata.tmdb_id.as_deref(), "TMDB", &format!("{prefix}metadata.tmdb_id")));
+  issues.extend(imdb_id_issue(metadata.imdb_id.as_deref(), &format!("{prefix}metadata.imdb_id")));
+
+  let mut seen_numbers = HashSet::new();
+  let mut previous: Option<EpisodeNumber> = None;
+
+  // Seasons start at episode 1, though specials (season 0) can also start at 0
+  let first_episodes: &[u32] = if metadata.season_number.value() == 0 { &[0, 1] } else { &[1] };
+
+  for (index, episode) in episodes_definition.episodes.iter().enumerate() {
+    let location = format!("{prefix}episodes.{index}.number");
+    let number = episode.number;
+
+    if !seen_numbers.insert(number) {
+      issues.push(Issue::new(&location, format!("Duplicate episode number: {number}")))
+    } else if let Some(previous) = previous.filter(|previous| number.episode() != previous.episode() + 1) {
+      issues.push(Issue::new(&location, format!("Episode number: {number} does not follow {previous}")))
+    } else if previous.is_none() && !first_episodes.contains(&number.episode()) {
+      issues.push(Issue::new(&location, format!("Episode number: {number} is not the first episode of the season")))
+    }
+
+    if number.season() != metadata.season_number {
+      issues.push(Issue::new(&location, format!("Episode number: {number} does not match season_number: {}", metadata.season_number)))
+    }
+
+    issues.extend(name_issue(&episode.name, &format!("{prefix}episodes.{index}.name")));
+    previous = Some(number);
+  }
+
+  for (index, extra) in episodes_definition.extras.iter().enumerate() {
+    issues.extend(name_issue(&extra.name, &format!("{prefix}extras.{index}.name")))
+  }
+
+  issues
+}
+
+fn movie_issues(metadata: &MovieMetadata) -> Vec<Issue> {
+  match metadata {
+    MovieMetadata::Single(movie) => movie_definition_issues(movie, ""),
+    MovieMetadata::BoxSet(box_set) => {
+      box_set
+        .movies
+        .iter()
+        .enumerate()
+        .flat_map(|(index, box_set_movie)| movie_definition_issues(&box_set_movie.movie, &format!("movies.{index}.")))
+        .collect()
+    }
+  }
+}
+
+fn movie_definition_issues(movie: &MovieDefinition, prefix: &str) -> Vec<Issue> {
+  [
+    name_issue(movie.name(), &format!("{prefix}name")),
+    missing_id_issue(movie.id_tags(), &format!("{prefix}tvdb_id")),
+    id_issue(movie.tvdb_id(), "TVDB", &format!("{prefix}tvdb_id")),
+    id_issue(movie.tmdb_id(), "TMDB", &format!("{prefix}tmdb_id")),
+    imdb_id_issue(movie.imdb_id(), &format!("{prefix}imdb_id")),
+  ]
+  .into_iter()
+  .flatten()
+  .collect()
+}
+
+fn name_issue(name: &str, location: &str) -> Option<Issue> {
+  let unsafe_chars: String =
+    name
+      .chars()
+      .filter(|c| UNSAFE_NAME_CHARS.contains(c) || c.is_control())
+      .collect();
+
+  if name.trim().is_empty() {
+    Some(Issue::new(location, "Name is empty".to_owned()))
+  } else if !unsafe_chars.is_empty() {
+    Some(Issue::new(location, format!("Name: '{name}' contains characters that can't be used in file names: {unsafe_chars}")))
+  } else if name.trim() != name {
+    Some(Issue::new(location, format!("Name: '{name}' has leading or trailing spaces")))
+  } else {
+    None
+  }
+}
+
+// The id tags are built from the TVDB or TMDB id, so one of them is needed
+fn missing_id_issue(id_tags: Option<String>, location: &str) -> Option<Issue> {
+  match id_tags {
+    Some(_) => None,
+    None => Some(Issue::new(location, "Neither tvdb_id nor tmdb_id is set. One is needed for the id tags in folder names".to_owned()))
+  }
+}
+
+fn id_issue(id: Option<&str>, provider: &str, location: &str) -> Option<Issue> {
+  id
+    .filter(|id| id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()))
+    .map(|id| Issue::new(location, format!("Invalid {provider} id: '{id}'. Ids are numbers")))
+}
+
+// Given: tt0088631, returns: None
+fn imdb_id_issue(id: Option<&str>, location: &str) -> Option<Issue> {
+  id
+    .filter(|id| !id.strip_prefix("tt").is_some_and(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())))
+    .map(|id| Issue::new(location, format!("Invalid IMDb id: '{id}'. Ids start with tt, such as tt0088631")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use tempfile::tempdir;
+    use crate::cli::{MkvCommands, MkvInputType, MkvRenamerArgs};
+    use pretty_assertions::assert_eq;
+
+    fn validate_args(path: &Path) -> ValidateArgs {
+      let args = MkvRenamerArgs::parse_from(["mkv-renamer", "series", "validate", "-f", &path.to_string_lossy()]);
+      match args.commands {
+        MkvInputType::Series(MkvCommands::Validate(validate_args)) => validate_args,
+        _ => unreachable!()
+      }
+    }
+
+    fn episode(number: &str, name: &str) -> EpisodeDefinition {
+      EpisodeDefinition { number: number.parse().unwrap(), name: name.to_owned(), absolute_number: None, air_date: None, runtime: None, overview: None }
+    }
+
+    #[test]
+    fn test_season_issues() {
+      let episodes_definition =
+        EpisodesDefinition {
+          metadata: SeriesMetaData {
+            name: "Thundercats".to_owned(),
+            tvdb_id: Some("tt70355".to_owned()),
+            tmdb_id: None,
+            imdb_id: Some("0088631".to_owned()),
+            season_number: "1".parse().unwrap(),
+            order: None,
+            language: None,
+            year: None
+          },
+          episodes: vec![
+            episode("S01E01", "Exodus"),
+            episode("S01E01", "The Unholy Alliance"),
+            episode("S01E03", "Berbils"),
+            episode("S02E04", "The Slaves of Castle Plun-Darr"),
+            episode("1x05", "Pumm-Ra"),
+            episode("S01E06", " "),
+            episode("S01E07", "Mumm-Ra: The Ever-Living"),
+          ],
+          extras: vec![]
+        };
+
+      let issues =
+        season_issues(&episodes_definition, "")
+          .into_iter()
+          .map(|issue| format!("{}: {}", issue.location, issue.message))
+          .collect::<Vec<_>>();
+
+      assert_eq!(
+        issues,
+        vec![
+          "metadata.tvdb_id: Invalid TVDB id: 'tt70355'. Ids are numbers",
+          "metadata.imdb_id: Invalid IMDb id: '0088631'. Ids start with tt, such as tt0088631",
+          "episodes.1.number: Duplicate episode number: S01E01",
+          "episodes.2.number: Episode number: S01E03 does not follow S01E01",
+          "episodes.3.number: Episode number: S02E04 does not match season_number: 1",
+          "episodes.5.name: Name is empty",
+          "episodes.6.name: Name: 'Mumm-Ra: The Ever-Living' contains characters that can't be used in file names: :",
+        ]
+      )
+    }
+
+    #[test]
+    fn test_first_episode_issues() {
+      let episodes_definition = |season_number: &str, numbers: &[&str]| {
+        EpisodesDefinition {
+          metadata: SeriesMetaData { name: "Thundercats".to_owned(), tvdb_id: Some("70355".to_owned()), tmdb_id: None, imdb_id: None, season_number: season_number.parse().unwrap(), order: None, language: None, year: None },
+          episodes: numbers.iter().map(|number| episode(number, "Exodus")).collect(),
+          extras: vec![]
+        }
+      };
+
+      let issues = |episodes_definition: EpisodesDefinition| season_issues(&episodes_definition, "").into_iter().map(|issue| format!("{}: {}", issue.location, issue.message)).collect::<Vec<_>>();
+
+      assert_eq!(issues(episodes_definition("1", &["S01E02", "S01E03"])), vec!["episodes.0.number: Episode number: S01E02 is not the first episode of the season"]);
+      assert_eq!(issues(episodes_definition("1", &["S01E00", "S01E01"])), vec!["episodes.0.number: Episode number: S01E00 is not the first episode of the season"]);
+      assert_eq!(issues(episodes_definition("0", &["S00E00", "S00E01"])), Vec::<String>::new());
+      assert_eq!(issues(episodes_definition("0", &["S00E01", "S00E02"])), Vec::<String>::new())
+    }
+
+    #[test]
+    fn test_movie_without_ids() {
+      let box_set = MovieMetadata::decode(MetadataFormat::Json, r#"{ "movies": [ { "disc":"disc1", "name":"Back to the Future", "tmdb_id":"105" }, { "disc":"disc2", "name":"Back to the Future Part II" } ] }"#).unwrap();
+
+      assert_eq!(
+        movie_issues(&box_set),
+        vec![Issue::new("movies.1.tvdb_id", "Neither tvdb_id nor tmdb_id is set. One is needed for the id tags in folder names".to_owned())]
+      )
+    }
+
+    #[test]
+    fn test_valid_series_file() {
+      let dir = tempdir().unwrap();
+      let path = dir.path().join("series.yaml");
+      fs::write(&path, "metadata:\n  name: Thundercats\n  tvdb_id: '70355'\n  season_number: '1'\nepisodes:\n  - number: S01E01\n    name: Exodus\n").unwrap();
+
+      assert!(matches!(perform(validate_args(&path), MediaKind::Series), Ok(Output::Valid)))
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+      let dir = tempdir().unwrap();
+      let path = dir.path().join("movie.json");
+      fs::write(&path, r#"{ "name": "The Big Lebowski", "tvdb_id": "659", "director": "Joel Coen" }"#).unwrap();
+
+      assert_eq!(unknown_field_issues::<MovieDefinition>(&path, MetadataFormat::Json).unwrap(), vec![Issue::new("director", "Unknown field. It will be ignored".to_owned())]);
+      assert!(matches!(perform(validate_args(&path), MediaKind::Movie), Err(RenamerError::InvalidMetadataFile(_, 1))))
+    }
+
+    #[test]
+    fn test_json_error_position() {
+      let dir = tempdir().unwrap();
+      let path = dir.path().join("series.json");
+      fs::write(&path, "{\n  \"metadata\": {\n    \"name\": \"Thundercats\",\n  }\n}").unwrap();
+
+      let result = perform(validate_args(&path), MediaKind::Series);
+      assert!(matches!(result, Err(RenamerError::CouldNotDecodeMetadataFile(_, _, message)) if message.contains("line 4 column 3")))
+    }
+
+    #[test]
+    fn test_invalid_field_error() {
+      let dir = tempdir().unwrap();
+      let path = dir.path().join("series.yaml");
+      fs::write(&path, "metadata:\n  name: Thundercats\n  tvdb_id: '70355'\n  season_number: '1'\nepisodes:\n  - number: Episode 5\n    name: Exodus\n").unwrap();
+
+      let result = perform(validate_args(&path), MediaKind::Series);
+      assert!(matches!(result, Err(RenamerError::CouldNotDecodeMetadataFile(_, _, message)) if message == "episodes[0]: Episode number: 'Episode 5' is not in S01E02, 1x02 or 102 format at line 6 column 5"))
+    }
+}
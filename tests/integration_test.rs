@@ -117,7 +117,7 @@ fn renames_tv_series_from_definition_file() -> Result<(), Box<dyn std::error::Er
       .arg("series")
       .arg("rename")
       .arg("-p")
-      .arg(&processing_dir_path)
+      .arg(processing_dir_path)
       .arg("-s")
       .arg("3")
       .arg("-f")
@@ -160,6 +160,101 @@ fn renames_tv_series_from_definition_file() -> Result<(), Box<dyn std::error::Er
 }
 
 
+#[test]
+fn renames_tv_series_extras_from_definition_file() -> Result<(), Box<dyn std::error::Error>> {
+    let processing_dir = tempdir().unwrap();
+    let processing_dir_path = processing_dir.path();
+
+    let rips = processing_dir_path.join("Rips");
+    let tv_encodes = processing_dir_path.join("Encodes").join("tv");
+
+    let tv_series_definition =
+      r#"{
+        "metadata": {
+          "name":"Thundercats",
+          "tvdb_id":"70355",
+          "season_number":"1"
+        },
+        "episodes": [
+          { "number":"S01E01", "name":"Exodus"},
+          { "number":"S01E02", "name":"The Unholy Alliance"}
+        ],
+        "extras": [
+          { "name":"Behind the Scenes"},
+          { "name":"Creating the Cats", "level":"series", "disc":"disc1", "title":"DVD_TS_03.mkv"}
+        ]
+    }"#;
+
+    let tv_series_definition_file_path = processing_dir_path.join("tv_series.conf");
+    let mut tv_series_definition_file = File::create(&tv_series_definition_file_path)?;
+    tv_series_definition_file.write_all(tv_series_definition.as_bytes())?;
+
+    let session = rips.join("session1");
+    let disc1 = session.join("disc1");
+    let extras_disc = session.join("disc2-extras");
+    let renames_dir = session.join("renames");
+
+    create_all_directories(&tv_encodes)?;
+    create_all_directories(&disc1)?;
+    create_all_directories(&extras_disc)?;
+    create_all_directories(&renames_dir)?;
+
+    for d in 1..=3 {
+      let _ = File::create(disc1.join(format!("DVD_TS_0{d}.mkv")))?;
+    }
+    let _ = File::create(extras_disc.join("DVD_TS_01.mkv"))?;
+
+    let mut cmd = Command::cargo_bin("mkv-renamer").unwrap();
+    cmd
+      .arg("series")
+      .arg("rename")
+      .arg("-p")
+      .arg(processing_dir_path)
+      .arg("-s")
+      .arg("1")
+      .arg("-f")
+      .arg(&tv_series_definition_file_path)
+      .write_stdin("y")
+      .assert()
+      .success();
+
+    let encodes_series_dir = tv_encodes.join("Thundercats {tvdb-70355} [tvdbid-70355]");
+    let encodes_season_dir = encodes_series_dir.join("Season 01");
+    let encodes_season_extras_dir = encodes_season_dir.join("Featurettes");
+    let encodes_series_extras_dir = encodes_series_dir.join("Featurettes");
+    let encodes_file = renames_dir.join("encode_dir.txt");
+
+    let expected_files: Vec<PathBuf> =
+      vec![
+        encodes_season_extras_dir.clone(),
+        encodes_series_extras_dir.clone(),
+        renames_dir.join("S01E01 - Exodus.mkv"),
+        renames_dir.join("S01E02 - The Unholy Alliance.mkv"),
+        renames_dir.join("Featurettes").join("Behind the Scenes.mkv"),
+        renames_dir.join("Series Featurettes").join("Creating the Cats.mkv"),
+      ];
+
+    for f in expected_files {
+      assert!(&f.exists(), "{} does not exist", &f.to_string_lossy());
+    }
+
+    let mut encodes_file_handle = File::open(&encodes_file)?;
+    let mut buffer = String::new();
+    encodes_file_handle.read_to_string(&mut buffer)?;
+
+    let expected_encodes =
+      [
+        encodes_season_dir.to_string_lossy(),
+        encodes_season_extras_dir.to_string_lossy(),
+        encodes_series_extras_dir.to_string_lossy()
+      ].join("\n");
+
+    assert_eq!(buffer, expected_encodes);
+
+    Ok(())
+}
+
+
 #[test]
 fn renames_movie_from_definition_file() -> Result<(), Box<dyn std::error::Error>> {
     //Inside processing dir:
@@ -244,7 +339,7 @@ fn renames_movie_from_definition_file() -> Result<(), Box<dyn std::error::Error>
       .arg("movie")
       .arg("rename")
       .arg("-p")
-      .arg(&processing_dir_path)
+      .arg(processing_dir_path)
       .arg("-s")
       .arg("1")
       .arg("-f")
@@ -280,6 +375,129 @@ fn renames_movie_from_definition_file() -> Result<(), Box<dyn std::error::Error>
     Ok(())
 }
 
+#[test]
+fn renames_movie_box_set_from_definition_file() -> Result<(), Box<dyn std::error::Error>> {
+    let processing_dir = tempdir().unwrap();
+    let processing_dir_path = processing_dir.path();
+
+    let rips = processing_dir_path.join("Rips");
+    let encodes = processing_dir_path.join("Encodes");
+    let movie_encodes = encodes.join("movies");
+
+    let movie_box_set_definition =
+      r#"{
+        "movies": [
+          { "disc":"disc1", "name":"Back to the Future", "tvdb_id":"296" },
+          { "disc":"disc2", "title":"title_t01.mkv", "name":"Back to the Future Part II", "tvdb_id":"297" }
+        ]
+      }"#;
+
+    let movie_definition_file_path = processing_dir_path.join("movie-box-set.conf");
+    let mut movie_definition_file = File::create(&movie_definition_file_path)?;
+    movie_definition_file.write_all(movie_box_set_definition.as_bytes())?;
+
+    create_all_directories(&movie_encodes)?;
+
+    let session = rips.join("session1");
+    let disc1 = session.join("disc1");
+    let disc2 = session.join("disc2");
+    let renames_dir = session.join("renames");
+
+    create_all_directories(&disc1)?;
+    create_all_directories(&disc2)?;
+    create_all_directories(&renames_dir)?;
+
+    let _ = File::create(disc1.join("title_t00.mkv"))?;
+    let _ = File::create(disc2.join("title_t01.mkv"))?;
+
+    let mut cmd = Command::cargo_bin("mkv-renamer").unwrap();
+    cmd
+      .arg("movie")
+      .arg("rename")
+      .arg("-p")
+      .arg(processing_dir_path)
+      .arg("-s")
+      .arg("1")
+      .arg("-f")
+      .arg(&movie_definition_file_path)
+      .write_stdin("y")
+      .assert()
+      .success();
+
+    let first_movie_dir = movie_encodes.join("Back to the Future - {tvdb-296} [tvdbid-296]");
+    let second_movie_dir = movie_encodes.join("Back to the Future Part II - {tvdb-297} [tvdbid-297]");
+    let encodes_file = renames_dir.join("encode_dir.txt");
+
+    let expected_files: Vec<PathBuf> =
+      vec![
+        first_movie_dir.clone(),
+        second_movie_dir.clone(),
+        renames_dir.join("Back to the Future - {tvdb-296} [tvdbid-296].mkv"),
+        renames_dir.join("Back to the Future Part II - {tvdb-297} [tvdbid-297].mkv"),
+        encodes_file.clone()
+      ];
+
+    for f in expected_files {
+      assert!(&f.exists(), "{} does not exist", &f.to_string_lossy());
+    }
+
+    let mut encodes_file_handle = File::open(&encodes_file)?;
+    let mut buffer = String::new();
+    encodes_file_handle.read_to_string(&mut buffer)?;
+
+    let expected_encodes = format!("{}\n{}", first_movie_dir.to_string_lossy(), second_movie_dir.to_string_lossy());
+    assert_eq!(buffer, expected_encodes);
+
+    Ok(())
+}
+
+#[test]
+fn exports_tv_series_from_html_file() -> Result<(), Box<dyn std::error::Error>> {
+    let export_dir = tempdir().unwrap();
+    let export_file_path = export_dir.path().join("series.json");
+
+    let mut cmd = Command::cargo_bin("mkv-renamer").unwrap();
+    cmd
+      .arg("series")
+      .arg("export")
+      .arg("--html-file")
+      .arg("tests/fixtures/html/x-men-evolution-season-1.html")
+      .arg("-e")
+      .arg(&export_file_path)
+      .assert()
+      .success();
+
+    let exported: serde_json::Value = serde_json::from_str(&fs::read_to_string(&export_file_path)?)?;
+
+    assert_eq!(exported["metadata"]["name"], "X-Men: Evolution");
+    assert_eq!(exported["metadata"]["tvdb_id"], "71389");
+    assert_eq!(exported["metadata"]["season_number"], "1");
+    assert_eq!(exported["episodes"][4]["name"], "Speed & Spyke");
+
+    Ok(())
+}
+
+#[test]
+fn exports_tv_series_to_stdout() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("mkv-renamer").unwrap();
+    let output =
+      cmd
+        .arg("series")
+        .arg("export")
+        .arg("--html-file")
+        .arg("tests/fixtures/html/x-men-evolution-season-1.html")
+        .arg("-e")
+        .arg("-")
+        .output()?;
+
+    assert!(output.status.success());
+
+    let exported: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(exported["metadata"]["name"], "X-Men: Evolution");
+
+    Ok(())
+}
+
 fn create_all_directories(p: &Path) -> Result<(), Box<dyn std::error::Error>> {
   if !p.exists() {
     fs::create_dir_all(p)?;